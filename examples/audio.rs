@@ -23,7 +23,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     // add some audio
     game.audio.add_audio("test", "./examples/assets/test.ogg");
@@ -36,7 +36,11 @@ fn main() {
             .instance_transforms(transforms)
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
+
+    // darken the creases between the cubes
+    game.renderer.post_effect.set_ssao(true);
 
     loop {
         // run the engine update
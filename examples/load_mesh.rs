@@ -25,7 +25,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     loop {
         // run the engine update
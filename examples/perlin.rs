@@ -45,8 +45,9 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
-    game.add_render_item(create_skydome("height"));
+    )
+    .unwrap();
+    game.add_render_item(create_skydome("height")).unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .name("sphere")
@@ -65,7 +66,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     game.add_text_item(
         TextItemBuilder::default()
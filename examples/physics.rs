@@ -26,7 +26,8 @@ fn main() {
             .physics_type(PhysicsType::Static)
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .vertices(gen_cube())
@@ -81,7 +82,8 @@ fn main() {
             .physics_type(PhysicsType::Dynamic)
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     loop {
         // clone of the RenderItem for access in the ui rendering
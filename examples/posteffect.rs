@@ -4,10 +4,33 @@ use caper::game::*;
 use caper::imgui::Ui;
 use caper::input::Key;
 use caper::mesh::gen_cube;
-use caper::posteffect::PostShaderOptionsBuilder;
+use caper::posteffect::{
+    PostPassBuilder, PostShaderOptionsBuilder, PostUniform, ToneMapOperator,
+    ToneMappingOptionsBuilder,
+};
+use caper::shader;
+use caper::transition::{TransitionOptionsBuilder, TransitionType};
 use caper::types::{DefaultTag, RenderItemBuilder, TransformBuilder};
 use caper::utils::handle_fp_inputs;
 
+// custom post pass that darkens the edges of the screen
+const VIGNETTE_FRAG: &str = "
+    #version 330
+
+    uniform sampler2D tex;
+    uniform float strength;
+
+    in vec2 v_tex_coords;
+
+    out vec4 frag_output;
+
+    void main() {
+        vec4 color = texture(tex, v_tex_coords);
+        float dist = distance(v_tex_coords, vec2(0.5));
+        frag_output = vec4(color.rgb * (1.0 - dist * strength), color.a);
+    }
+";
+
 fn main() {
     // crate an instance of the game struct
     let mut game = Game::<DefaultTag>::new();
@@ -24,7 +47,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     // example of how to configure the default post effect shader
     game.renderer.post_effect.post_shader_options = PostShaderOptionsBuilder::default()
@@ -40,6 +64,39 @@ fn main() {
         .build()
         .unwrap();
 
+    // render the scene in HDR with filmic tone mapping and eye adaptation
+    game.renderer.post_effect.tone_mapping = ToneMappingOptionsBuilder::default()
+        .operator(ToneMapOperator::Filmic)
+        .auto_exposure(true)
+        .build()
+        .unwrap();
+    game.renderer.post_effect.set_hdr(true);
+
+    // bloom the brightest parts of the scene
+    game.renderer.post_effect.bloom.intensity = 0.2f32;
+    game.renderer.post_effect.set_bloom(true);
+
+    // add a custom pass that runs after the default post pass
+    game.renderer
+        .shaders
+        .add_post_shader(
+            &game.renderer.display,
+            "vignette",
+            shader::post::gl330::VERT,
+            VIGNETTE_FRAG,
+        )
+        .unwrap();
+    let mut vignette = PostPassBuilder::default()
+        .name("vignette")
+        .shader_name("vignette")
+        .build()
+        .unwrap();
+    vignette.set_uniform("strength", PostUniform::Float(0.8f32));
+    game.renderer
+        .post_effect
+        .add_pass(&game.renderer.shaders, vignette)
+        .unwrap();
+
     loop {
         // run the engine update
         let status = game.update(
@@ -48,6 +105,18 @@ fn main() {
                 // update the first person inputs
                 handle_fp_inputs(&mut g.input, &mut g.cams[0]);
 
+                // fade to white and back
+                if g.input.keys_down.contains(&Key::T)
+                    && !g.renderer.post_effect.transition_active()
+                {
+                    let fade = TransitionOptionsBuilder::default()
+                        .transition_type(TransitionType::Fade((1f32, 1f32, 1f32)))
+                        .duration(2f32)
+                        .build()
+                        .unwrap();
+                    g.start_transition(fade, |_: &mut Game<DefaultTag>| println!("screen covered"));
+                }
+
                 // quit
                 if g.input.keys_down.contains(&Key::Escape) {
                     return UpdateStatus::Finish;
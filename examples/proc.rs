@@ -36,7 +36,8 @@ fn main() {
             .instance_transforms(transforms)
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     loop {
         // run the engine update
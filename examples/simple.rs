@@ -23,7 +23,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     loop {
         // run the engine update
@@ -23,7 +23,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .vertices(gen_sphere_segments(10f32, 5f32))
@@ -35,7 +36,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .vertices(gen_sphere_segments(5f32, 10f32))
@@ -47,7 +49,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     loop {
         // run the engine update
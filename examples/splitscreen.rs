@@ -0,0 +1,71 @@
+extern crate caper;
+
+use caper::game::*;
+use caper::imgui::Ui;
+use caper::input::Key;
+use caper::mesh::gen_cube;
+use caper::types::{CameraBuilder, DefaultTag, RenderItemBuilder, TransformBuilder};
+use caper::utils::{handle_fp_inputs, split_screen_viewports};
+
+fn main() {
+    // crate an instance of the game struct
+    let mut game = Game::<DefaultTag>::new();
+
+    // define some items to be rendered
+    game.add_render_item(
+        RenderItemBuilder::default()
+            .vertices(gen_cube())
+            .instance_transforms(vec![
+                TransformBuilder::default()
+                    .pos((-0.5, 0.0, -5.0))
+                    .build()
+                    .unwrap(),
+            ])
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+
+    // two player split screen
+    let viewports = split_screen_viewports(2);
+    game.cams[0].viewport = viewports[0];
+    game.cams.push(
+        CameraBuilder::default()
+            .pos((0.0, 2.0, 0.0))
+            .viewport(viewports[1])
+            .build()
+            .unwrap(),
+    );
+
+    // picture in picture in the top right corner
+    game.cams.push(
+        CameraBuilder::default()
+            .pos((-0.5, 10.0, -5.0))
+            .euler_rot((1.57, 0.0, 0.0))
+            .viewport((0.75, 0.75, 0.2, 0.2))
+            .build()
+            .unwrap(),
+    );
+
+    loop {
+        // run the engine update
+        let status = game.update(
+            |_: &Ui| {},
+            |g: &mut Game<DefaultTag>| -> UpdateStatus {
+                // update the first person inputs
+                handle_fp_inputs(&mut g.input, &mut g.cams[0]);
+
+                // quit
+                if g.input.keys_down.contains(&Key::Escape) {
+                    return UpdateStatus::Finish;
+                }
+
+                UpdateStatus::Continue
+            },
+        );
+
+        if let UpdateStatus::Finish = status {
+            break;
+        }
+    }
+}
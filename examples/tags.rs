@@ -40,7 +40,8 @@ fn main() {
             .tag(Tags::One)
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .vertices(gen_cube())
@@ -53,7 +54,8 @@ fn main() {
             .tag(Tags::Two)
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     loop {
         // run the engine update
@@ -53,7 +53,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .name("floor")
@@ -76,7 +77,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .vertices(gen_quad())
@@ -94,7 +96,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .vertices(gen_sphere())
@@ -112,7 +115,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
     game.add_render_item(
         RenderItemBuilder::default()
             .vertices(gen_cube())
@@ -124,7 +128,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     game.renderer
         .lighting
@@ -0,0 +1,417 @@
+use input::{Input, Key};
+use types::{Camera, OrientationType, Quaternion, RenderItem, Vector3};
+use utils::{
+    build_fp_view_matrix, clamp_pitch, crossp, dotp, mul_quat, normalize_quat, quat_from_axes,
+    rotate_vec3, wrap_angle,
+};
+
+impl Camera {
+    /// Returns the world space right, up and back axes of the camera
+    pub fn axes(&self) -> (Vector3, Vector3, Vector3) {
+        match self.orientation_type {
+            OrientationType::Euler => {
+                let (sin_yaw, cos_yaw) = self.euler_rot.1.sin_cos();
+                let (sin_pitch, cos_pitch) = self.euler_rot.0.sin_cos();
+                (
+                    (cos_yaw, 0f32, -sin_yaw),
+                    (sin_yaw * sin_pitch, cos_pitch, cos_yaw * sin_pitch),
+                    (sin_yaw * cos_pitch, -sin_pitch, cos_pitch * cos_yaw),
+                )
+            }
+            OrientationType::Quaternion => {
+                let rot = normalize_quat(self.rot);
+                (
+                    rotate_vec3(rot, (1f32, 0f32, 0f32)),
+                    rotate_vec3(rot, (0f32, 1f32, 0f32)),
+                    rotate_vec3(rot, (0f32, 0f32, 1f32)),
+                )
+            }
+        }
+    }
+
+    /// Returns the direction the camera is facing in world space
+    pub fn forward(&self) -> Vector3 {
+        let (_, _, back) = self.axes();
+        (-back.0, -back.1, -back.2)
+    }
+
+    /// Returns the direction to the right of the camera in world space
+    pub fn right(&self) -> Vector3 {
+        self.axes().0
+    }
+
+    /// Returns the up direction of the camera in world space
+    pub fn up(&self) -> Vector3 {
+        self.axes().1
+    }
+
+    /// Returns the orientation of the camera as a quaternion in either orientation_type
+    pub fn orientation(&self) -> Quaternion {
+        match self.orientation_type {
+            OrientationType::Euler => {
+                let (right, up, back) = self.axes();
+                quat_from_axes(right, up, back)
+            }
+            OrientationType::Quaternion => normalize_quat(self.rot),
+        }
+    }
+
+    /// Rotates the camera by rot in its local space, switching to quaternion orientation
+    pub fn rotate(&mut self, rot: Quaternion) {
+        self.rot = normalize_quat(mul_quat(self.orientation(), rot));
+        self.orientation_type = OrientationType::Quaternion;
+    }
+
+    /// Orients the camera to face target with no roll
+    pub fn look_at(&mut self, target: Vector3) {
+        match self.orientation_type {
+            OrientationType::Euler => self.euler_rot = look_at_euler(self.pos, target),
+            OrientationType::Quaternion => {
+                let fwd = [
+                    target.0 - self.pos.0,
+                    target.1 - self.pos.1,
+                    target.2 - self.pos.2,
+                ];
+                let len = dotp(&fwd, &fwd).sqrt();
+                if len <= 0f32 {
+                    return;
+                }
+                let fwd = [fwd[0] / len, fwd[1] / len, fwd[2] / len];
+
+                // fall back to the x axis when looking straight up or down
+                let right = crossp(fwd, [0f32, 1f32, 0f32]);
+                let right_len = dotp(&right, &right).sqrt();
+                let right = if right_len <= 0.0001f32 {
+                    [1f32, 0f32, 0f32]
+                } else {
+                    [
+                        right[0] / right_len,
+                        right[1] / right_len,
+                        right[2] / right_len,
+                    ]
+                };
+                let up = crossp(right, fwd);
+
+                self.rot = quat_from_axes(
+                    (right[0], right[1], right[2]),
+                    (up[0], up[1], up[2]),
+                    (-fwd[0], -fwd[1], -fwd[2]),
+                );
+            }
+        }
+    }
+
+    /// Converts a point from the camera's local space to world space
+    pub fn camera_to_world(&self, point: Vector3) -> Vector3 {
+        let (right, up, back) = self.axes();
+        (
+            self.pos.0 + right.0 * point.0 + up.0 * point.1 + back.0 * point.2,
+            self.pos.1 + right.1 * point.0 + up.1 * point.1 + back.1 * point.2,
+            self.pos.2 + right.2 * point.0 + up.2 * point.1 + back.2 * point.2,
+        )
+    }
+
+    /// Converts a point from world space to the camera's local space
+    pub fn world_to_camera(&self, point: Vector3) -> Vector3 {
+        let (right, up, back) = self.axes();
+        let rel = [
+            point.0 - self.pos.0,
+            point.1 - self.pos.1,
+            point.2 - self.pos.2,
+        ];
+        (
+            dotp(&rel, &[right.0, right.1, right.2]),
+            dotp(&rel, &[up.0, up.1, up.2]),
+            dotp(&rel, &[back.0, back.1, back.2]),
+        )
+    }
+}
+
+/// Trait for types that move a Camera in response to input
+pub trait CameraController {
+    /// Update the camera from the input, with movement scaled by the frame delta
+    fn update(&mut self, input: &Input, cam: &mut Camera, delta: f32);
+}
+
+/// The keys used by the camera controllers
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct KeyBindings {
+    /// Key to move forward
+    pub forward: Key,
+    /// Key to move backward
+    pub back: Key,
+    /// Key to move left
+    pub left: Key,
+    /// Key to move right
+    pub right: Key,
+    /// Key to move up
+    pub up: Key,
+    /// Key to move down
+    pub down: Key,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            forward: Key::W,
+            back: Key::S,
+            left: Key::A,
+            right: Key::D,
+            up: Key::E,
+            down: Key::Q,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Returns the (right, up, forward) movement axes from the keys currently down
+    pub fn axes(&self, input: &Input) -> Vector3 {
+        let axis = |pos: &Key, neg: &Key| {
+            let mut val = 0f32;
+            if input.keys_down.contains(pos) {
+                val += 1f32;
+            }
+            if input.keys_down.contains(neg) {
+                val -= 1f32;
+            }
+            val
+        };
+
+        (
+            axis(&self.right, &self.left),
+            axis(&self.up, &self.down),
+            axis(&self.forward, &self.back),
+        )
+    }
+}
+
+/// Applies the mouse motion to the camera rotation, clamping the pitch
+fn apply_mouse_look(input: &Input, cam: &mut Camera, mouse_speed: f32) {
+    cam.euler_rot.0 = clamp_pitch(cam.euler_rot.0 + input.mouse_axis_motion.1 * mouse_speed);
+    cam.euler_rot.1 = wrap_angle(cam.euler_rot.1 + input.mouse_axis_motion.0 * mouse_speed);
+}
+
+/// Returns the euler rotation for a camera at pos looking towards target
+pub fn look_at_euler(pos: Vector3, target: Vector3) -> Vector3 {
+    let dir = (target.0 - pos.0, target.1 - pos.1, target.2 - pos.2);
+    let len = (dir.0 * dir.0 + dir.1 * dir.1 + dir.2 * dir.2).sqrt();
+    if len <= 0f32 {
+        return (0f32, 0f32, 0f32);
+    }
+
+    let pitch = (dir.1 / len).asin();
+    let yaw = wrap_angle((-dir.0).atan2(-dir.2));
+
+    (pitch, yaw, 0f32)
+}
+
+/// First person controller that moves along the ground plane
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct FirstPersonController {
+    /// Movement speed in units per second
+    pub move_speed: f32,
+    /// Rotation per unit of mouse motion
+    pub mouse_speed: f32,
+    /// The keys used for movement
+    pub bindings: KeyBindings,
+}
+
+impl Default for FirstPersonController {
+    fn default() -> Self {
+        FirstPersonController {
+            move_speed: 12f32,
+            mouse_speed: 1f32,
+            bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl CameraController for FirstPersonController {
+    fn update(&mut self, input: &Input, cam: &mut Camera, delta: f32) {
+        let (right, _, forward) = self.bindings.axes(input);
+        let (sin_yaw, cos_yaw) = cam.euler_rot.1.sin_cos();
+        let speed = self.move_speed * delta;
+
+        // forward and right flattened on to the ground plane
+        cam.pos.0 += (cos_yaw * right - sin_yaw * forward) * speed;
+        cam.pos.2 += (-sin_yaw * right - cos_yaw * forward) * speed;
+
+        apply_mouse_look(input, cam, self.mouse_speed);
+    }
+}
+
+/// Free flying controller that moves along the view direction and vertically
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct FreeFlyController {
+    /// Movement speed in units per second
+    pub move_speed: f32,
+    /// Rotation per unit of mouse motion
+    pub mouse_speed: f32,
+    /// The keys used for movement
+    pub bindings: KeyBindings,
+}
+
+impl Default for FreeFlyController {
+    fn default() -> Self {
+        FreeFlyController {
+            move_speed: 12f32,
+            mouse_speed: 1f32,
+            bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl CameraController for FreeFlyController {
+    fn update(&mut self, input: &Input, cam: &mut Camera, delta: f32) {
+        let (right, up, forward) = self.bindings.axes(input);
+        let mv_matrix = build_fp_view_matrix(cam);
+        let speed = self.move_speed * delta;
+
+        // the view matrix rows hold the camera right and back directions
+        cam.pos.0 += (mv_matrix[0][0] * right - mv_matrix[0][2] * forward) * speed;
+        cam.pos.1 += (mv_matrix[1][0] * right - mv_matrix[1][2] * forward + up) * speed;
+        cam.pos.2 += (mv_matrix[2][0] * right - mv_matrix[2][2] * forward) * speed;
+
+        apply_mouse_look(input, cam, self.mouse_speed);
+    }
+}
+
+/// Controller that orbits the camera around a target point
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct OrbitController {
+    /// The point the camera orbits around
+    pub target: Vector3,
+    /// The distance from the target
+    pub distance: f32,
+    /// The closest the camera can zoom to the target
+    pub min_distance: f32,
+    /// The furthest the camera can zoom from the target
+    pub max_distance: f32,
+    /// Zoom speed in units per second
+    pub zoom_speed: f32,
+    /// Rotation speed in radians per second when using the keys
+    pub rotate_speed: f32,
+    /// Rotation per unit of mouse motion
+    pub mouse_speed: f32,
+    /// The keys used for rotating and zooming
+    pub bindings: KeyBindings,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        OrbitController {
+            target: (0f32, 0f32, 0f32),
+            distance: 10f32,
+            min_distance: 1f32,
+            max_distance: 100f32,
+            zoom_speed: 10f32,
+            rotate_speed: 1.5f32,
+            mouse_speed: 1f32,
+            bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl CameraController for OrbitController {
+    fn update(&mut self, input: &Input, cam: &mut Camera, delta: f32) {
+        let (right, up, forward) = self.bindings.axes(input);
+
+        // forward/back zooms, the other axes rotate around the target
+        self.distance = (self.distance - forward * self.zoom_speed * delta)
+            .max(self.min_distance)
+            .min(self.max_distance);
+        cam.euler_rot.0 -= up * self.rotate_speed * delta;
+        cam.euler_rot.1 += right * self.rotate_speed * delta;
+        apply_mouse_look(input, cam, self.mouse_speed);
+
+        // place the camera behind its view direction
+        let (sin_yaw, cos_yaw) = cam.euler_rot.1.sin_cos();
+        let (sin_pitch, cos_pitch) = cam.euler_rot.0.sin_cos();
+        cam.pos = (
+            self.target.0 + sin_yaw * cos_pitch * self.distance,
+            self.target.1 - sin_pitch * self.distance,
+            self.target.2 + cos_yaw * cos_pitch * self.distance,
+        );
+    }
+}
+
+/// Controller that smoothly follows a RenderItem instance in third person
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct FollowController {
+    /// The name of the RenderItem being followed
+    #[builder(setter(into))]
+    pub item_name: String,
+    /// The index of the instance of the RenderItem being followed
+    pub instance: usize,
+    /// The offset from the target in the target's local space
+    pub offset: Vector3,
+    /// The offset from the target position the camera looks at
+    pub look_offset: Vector3,
+    /// How quickly the camera catches up with the target, higher is tighter
+    pub smoothing: f32,
+}
+
+impl Default for FollowController {
+    fn default() -> Self {
+        FollowController {
+            item_name: "ri".into(),
+            instance: 0,
+            offset: (0f32, 2f32, 6f32),
+            look_offset: (0f32, 1f32, 0f32),
+            smoothing: 5f32,
+        }
+    }
+}
+
+impl FollowController {
+    /// Moves the camera towards the followed instance and looks at it,
+    /// with movement scaled by the frame delta
+    pub fn follow<T: Default>(
+        &self,
+        render_items: &[RenderItem<T>],
+        cam: &mut Camera,
+        delta: f32,
+    ) -> Result<(), String> {
+        let target = render_items
+            .iter()
+            .find(|r| r.name == self.item_name)
+            .ok_or_else(|| format!("No render item named {}", self.item_name))?
+            .instance_transforms
+            .get(self.instance)
+            .ok_or_else(|| {
+                format!(
+                    "Render item {} has no instance {}",
+                    self.item_name, self.instance
+                )
+            })?;
+
+        let offset = rotate_vec3(target.rot, self.offset);
+        let desired = (
+            target.pos.0 + offset.0,
+            target.pos.1 + offset.1,
+            target.pos.2 + offset.2,
+        );
+
+        // frame rate independent exponential smoothing
+        let t = 1f32 - (-self.smoothing * delta).exp();
+        cam.pos = (
+            cam.pos.0 + (desired.0 - cam.pos.0) * t,
+            cam.pos.1 + (desired.1 - cam.pos.1) * t,
+            cam.pos.2 + (desired.2 - cam.pos.2) * t,
+        );
+
+        let look_target = (
+            target.pos.0 + self.look_offset.0,
+            target.pos.1 + self.look_offset.1,
+            target.pos.2 + self.look_offset.2,
+        );
+        cam.euler_rot = look_at_euler(cam.pos, look_target);
+
+        Ok(())
+    }
+}
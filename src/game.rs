@@ -1,6 +1,7 @@
 use audio::Audio;
 use imgui::Ui;
 use input::Input;
+use renderer::transition::TransitionOptions;
 use renderer::{Draw, Renderer};
 use types::{Camera, PhysicsType, RenderItem, TextItem};
 
@@ -60,6 +61,8 @@ pub struct Game<T: Default> {
     physics_items: Vec<PhysicsHandle>,
     /// The delta time for each frame
     pub delta: f32,
+    /// Called when the running screen transition covers the screen
+    transition_midpoint: Option<Box<FnMut(&mut Game<T>)>>,
 }
 
 impl<T: Default> Game<T> {
@@ -70,10 +73,7 @@ impl<T: Default> Game<T> {
         physics.set_gravity(nVector3::new(0.0, -9.81, 0.0));
 
         //cam state
-        let cam = Camera {
-            pos: (0.0f32, 0.0, 0.0),
-            euler_rot: (0.0f32, 0.0, 0.0),
-        };
+        let cam = Camera::default();
 
         let events_loop = EventsLoop::new();
 
@@ -89,6 +89,29 @@ impl<T: Default> Game<T> {
             text_items: Vec::new(),
             physics_items: Vec::new(),
             delta: 0.016_666_667f32,
+            transition_midpoint: None,
+        }
+    }
+
+    /// Start a screen transition, replacing any running one,
+    /// on_midpoint is called during update when the screen is covered so the scene can be swapped
+    pub fn start_transition<F>(&mut self, options: TransitionOptions, on_midpoint: F)
+    where
+        F: FnMut(&mut Game<T>) + 'static,
+    {
+        self.renderer.post_effect.start_transition(options);
+        self.transition_midpoint = Some(Box::new(on_midpoint));
+    }
+
+    /// Advance the screen transition, calling its midpoint callback once the screen is covered
+    fn update_transition(&mut self) {
+        if self.renderer.post_effect.update_transition(self.delta) {
+            if let Some(mut on_midpoint) = self.transition_midpoint.take() {
+                on_midpoint(self);
+            }
+        }
+        if !self.renderer.post_effect.transition_active() {
+            self.transition_midpoint = None;
         }
     }
 }
@@ -113,8 +136,8 @@ pub trait RenderItems {
     fn get_render_item(&mut self, index: usize) -> &mut RenderItem<Self::T>;
     /// Get a ref to a render item from its name, returning the first found
     fn get_render_item_by_name(&mut self, name: &str) -> Option<&mut RenderItem<Self::T>>;
-    /// Add a render item to the game
-    fn add_render_item(&mut self, render_item: RenderItem<Self::T>);
+    /// Add a render item to the game, failing if its material uses a missing shader or texture
+    fn add_render_item(&mut self, render_item: RenderItem<Self::T>) -> Result<(), String>;
 }
 
 impl<T: Default> RenderItems for Game<T> {
@@ -140,8 +163,14 @@ impl<T: Default> RenderItems for Game<T> {
         self.render_items.iter_mut().find(|item| item.name == name)
     }
 
-    /// Add a render item to the game
-    fn add_render_item(&mut self, render_item: RenderItem<T>) {
+    /// Add a render item to the game, failing if its material uses a missing shader or texture
+    fn add_render_item(&mut self, render_item: RenderItem<T>) -> Result<(), String> {
+        // check the material before the item is drawn
+        self.renderer
+            .shaders
+            .validate_material(&render_item.material, render_item.topology)
+            .map_err(|e| format!("Render item {}: {}", render_item.name, e))?;
+
         // add the render item
         self.render_items.push(render_item);
 
@@ -150,6 +179,8 @@ impl<T: Default> RenderItems for Game<T> {
 
         // setup the physics for the item
         self.add_physics(i);
+
+        Ok(())
     }
 }
 
@@ -381,6 +412,7 @@ impl<T: Default> Update for Game<T> {
         self.update_physics();
 
         let status = update(self);
+        self.update_transition();
 
         // render the frame
         {
@@ -45,7 +45,8 @@ fn main() {
             ])
             .build()
             .unwrap(),
-    );
+    )
+    .unwrap();
 
     loop {
         // run the engine update
@@ -95,10 +96,13 @@ extern crate gif;
 extern crate glium_text_rusttype as glium_text;
 extern crate imgui_glium_renderer;
 extern crate noise;
+extern crate png;
 extern crate rayon;
 extern crate serde;
 extern crate time;
 
+/// Camera controllers for moving cameras from input
+pub mod camera;
 /// Simple collision detection
 pub mod collision;
 /// Module represent another way of creating a game
@@ -114,7 +118,12 @@ pub mod types;
 /// Utility functions and macros
 pub mod utils;
 
+pub use renderer::fog;
 pub use renderer::lighting;
+pub use renderer::lut;
 pub use renderer::posteffect;
 pub use renderer::shader;
+pub use renderer::sky;
+pub use renderer::skybox;
 pub use renderer::texture;
+pub use renderer::transition;
@@ -1,5 +1,12 @@
+use image;
+use image::{DynamicImage, ImageError, ImageResult};
 use noise::{NoiseFn, Perlin, Seedable};
+use png;
+use png::HasParameters;
 use std::f32::consts::PI;
+use std::fs::File;
+use std::io;
+use std::path::Path;
 use types::Vertex;
 use utils::calc_normal;
 
@@ -10,6 +17,8 @@ pub const DEF_UV: [f32; 2] = [0f32, 0f32];
 const PI2: f32 = PI * 2f32;
 /// The default seed base for creating a perlin mesh
 pub const DEF_SEED_BASE: u32 = 0;
+/// The maximum height returned by get_pos_perlin
+pub const PERLIN_HEIGHT: f32 = 6f32;
 
 lazy_static! {
     /// static ref to generate perlin noise from
@@ -319,7 +328,7 @@ pub fn set_perlin_seed(seed: u32) {
 pub fn get_pos_perlin(p: (f32, f32)) -> f32 {
     PERLIN
         .get([f64::from(p.0) / 15f64, f64::from(p.1) / 15f64])
-        .abs() as f32 * 6f32
+        .abs() as f32 * PERLIN_HEIGHT
 }
 
 /// Generates a perlin mesh from pseu_pos with each side of vert length map_size
@@ -390,3 +399,154 @@ pub fn gen_proc_mesh(
 
     vertices
 }
+
+/// Generates a terrain mesh from a greyscale heightmap image, with white being height_scale
+/// and each pixel spacing apart
+pub fn gen_heightmap_mesh(
+    heightmap: &DynamicImage,
+    height_scale: f32,
+    spacing: f32,
+) -> Vec<Vertex> {
+    let heightmap = heightmap.to_luma();
+    let (width, height) = heightmap.dimensions();
+    let heights = heightmap
+        .pixels()
+        .map(|p| f32::from(p.data[0]) / 255f32)
+        .collect::<Vec<f32>>();
+
+    gen_height_grid_mesh(&heights, (width, height), height_scale, spacing)
+}
+
+/// Generates a terrain mesh from rows of heights from 0 to 1, with 1 being height_scale
+/// and each height spacing apart
+pub fn gen_height_grid_mesh(
+    heights: &[f32],
+    dimensions: (u32, u32),
+    height_scale: f32,
+    spacing: f32,
+) -> Vec<Vertex> {
+    let (width, height) = dimensions;
+    let mut vertices = Vec::new();
+
+    // need at least a 2x2 grid to make a quad
+    if width < 2 || height < 2 || heights.len() < (width * height) as usize {
+        return vertices;
+    }
+
+    let get_height = |x: u32, y: u32| heights[(y * width + x) as usize] * height_scale;
+
+    for y in 0..height - 1 {
+        for x in 0..width - 1 {
+            let pos = (x as f32 * spacing, y as f32 * spacing);
+
+            let size_00 = get_height(x, y);
+            let size_10 = get_height(x + 1, y);
+            let size_01 = get_height(x, y + 1);
+            let size_11 = get_height(x + 1, y + 1);
+
+            // create the two tris for this pixel
+            push_vertices![
+                vertices,
+                [pos.0 + spacing, size_10, pos.1],
+                [pos.0, size_00, pos.1],
+                [pos.0 + spacing, size_11, pos.1 + spacing],
+                [pos.0, size_00, pos.1],
+                [pos.0, size_01, pos.1 + spacing],
+                [pos.0 + spacing, size_11, pos.1 + spacing],
+            ];
+        }
+    }
+
+    vertices
+}
+
+/// Loads a greyscale heightmap image from path and generates a terrain mesh from it,
+/// 16-bit pngs are read at their full precision
+pub fn load_heightmap_mesh<P: AsRef<Path>>(
+    path: P,
+    height_scale: f32,
+    spacing: f32,
+) -> ImageResult<Vec<Vertex>> {
+    let path = path.as_ref();
+    let is_png = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("png"));
+
+    if is_png {
+        if let Some((heights, dimensions)) = read_png16_heights(path)? {
+            return Ok(gen_height_grid_mesh(
+                &heights,
+                dimensions,
+                height_scale,
+                spacing,
+            ));
+        }
+    }
+
+    let heightmap = image::open(path)?;
+    Ok(gen_heightmap_mesh(&heightmap, height_scale, spacing))
+}
+
+/// Reads the first channel of a 16-bit png as heights from 0 to 1,
+/// returning None for other bit depths which image reads without losing precision
+fn read_png16_heights(path: &Path) -> ImageResult<Option<(Vec<f32>, (u32, u32))>> {
+    let mut decoder = png::Decoder::new(File::open(path)?);
+    // the default transformations strip 16-bit samples to 8 bits
+    decoder.set(png::Transformations::IDENTITY);
+    let (info, mut reader) = decoder
+        .read_info()
+        .map_err(|e| ImageError::FormatError(e.to_string()))?;
+    if info.bit_depth != png::BitDepth::Sixteen {
+        return Ok(None);
+    }
+
+    let mut buf = vec![0u8; info.buffer_size()];
+    reader
+        .next_frame(&mut buf)
+        .map_err(|e| ImageError::FormatError(e.to_string()))?;
+
+    let stride = info.color_type.samples() * 2;
+    let heights = buf
+        .chunks(info.line_size)
+        .take(info.height as usize)
+        .flat_map(|line| line.chunks(stride).take(info.width as usize))
+        // png stores 16-bit samples big endian
+        .map(|pixel| f32::from(u16::from(pixel[0]) << 8 | u16::from(pixel[1])) / 65535f32)
+        .collect();
+
+    Ok(Some((heights, (info.width, info.height))))
+}
+
+/// Samples gen_fn from pseu_pos over map_size points on each side and saves the heights
+/// to a 16-bit greyscale png, with height_scale being mapped to white
+pub fn save_proc_heightmap<P: AsRef<Path>>(
+    path: P,
+    pseu_pos: (f32, f32),
+    map_size: u32,
+    height_scale: f32,
+    gen_fn: fn((f32, f32)) -> f32,
+) -> io::Result<()> {
+    let mut buf = Vec::with_capacity((map_size * map_size * 2) as usize);
+
+    for y in 0..map_size {
+        for x in 0..map_size {
+            let h = gen_fn((pseu_pos.0 + x as f32, pseu_pos.1 + y as f32)) / height_scale;
+            let h = (h.max(0f32).min(1f32) * f32::from(u16::max_value())) as u16;
+            // png stores 16-bit samples big endian
+            buf.push((h >> 8) as u8);
+            buf.push(h as u8);
+        }
+    }
+
+    image::save_buffer(path, &buf, map_size, map_size, image::Gray(16))
+}
+
+/// Samples the perlin heightfield from pseu_pos and saves it to a 16-bit greyscale png
+pub fn save_perlin_heightmap<P: AsRef<Path>>(
+    path: P,
+    pseu_pos: (f32, f32),
+    map_size: u32,
+) -> io::Result<()> {
+    save_proc_heightmap(path, pseu_pos, map_size, PERLIN_HEIGHT, get_pos_perlin)
+}
@@ -0,0 +1,97 @@
+use types::Vector3;
+
+/// How fog thickens with distance
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FogMode {
+    /// Fades in evenly from the start to the end distance
+    Linear,
+    /// Fades in exponentially past the start distance
+    Exponential,
+    /// Fades in exponentially squared past the start distance
+    ExponentialSquared,
+}
+
+/// The distance fog applied by the built in shaders
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct Fog {
+    /// Whether the fog is applied
+    pub active: bool,
+    /// How the fog thickens with distance
+    pub mode: FogMode,
+    /// The colour distant objects fade to, overridden by the sky when there is one
+    pub color: Vector3,
+    /// How quickly exponential fog thickens
+    pub density: f32,
+    /// The distance from the camera the fog starts at
+    pub start: f32,
+    /// The distance linear fog is fully opaque at
+    pub end: f32,
+    /// How quickly the fog thins out above y = 0, 0 for even fog at every height
+    pub height_falloff: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Fog {
+            active: true,
+            mode: FogMode::Linear,
+            color: (1f32, 1f32, 1f32),
+            density: 0.02f32,
+            start: 0f32,
+            end: 80f32,
+            height_falloff: 0f32,
+        }
+    }
+}
+
+impl FogMode {
+    /// The mode as passed to the shaders as fog_mode,
+    /// these must match the FOG_* defines of the "fog" shader chunk
+    pub fn mode_index(&self) -> i32 {
+        match *self {
+            FogMode::Linear => 0,
+            FogMode::Exponential => 1,
+            FogMode::ExponentialSquared => 2,
+        }
+    }
+
+    /// The mode from its index, unknown indices are Linear
+    pub fn from_index(index: i32) -> FogMode {
+        match index {
+            1 => FogMode::Exponential,
+            2 => FogMode::ExponentialSquared,
+            _ => FogMode::Linear,
+        }
+    }
+}
+
+impl Fog {
+    /// The fog mode as passed to the shaders as fog_mode
+    pub fn mode_index(&self) -> i32 {
+        self.mode.mode_index()
+    }
+
+    /// How much of the fog colour shows at pos seen from cam_pos, from 0 to 1,
+    /// matching scene_fog_amount in the "scene_fog" shader chunk
+    pub fn amount(&self, cam_pos: Vector3, pos: Vector3) -> f32 {
+        if !self.active {
+            return 0f32;
+        }
+
+        let offset = (pos.0 - cam_pos.0, pos.1 - cam_pos.1, pos.2 - cam_pos.2);
+        let dist = (offset.0 * offset.0 + offset.1 * offset.1 + offset.2 * offset.2).sqrt();
+        let d = (dist - self.start).max(0f32);
+        let amount = match self.mode {
+            FogMode::Linear => (d / (self.end - self.start).max(0.0001f32)).min(1f32),
+            FogMode::Exponential => 1f32 - (-d * self.density).exp(),
+            FogMode::ExponentialSquared => {
+                let d = d * self.density;
+                1f32 - (-d * d).exp()
+            }
+        };
+
+        // thins out above y = 0
+        amount * (-self.height_falloff * pos.1.max(0f32)).exp()
+    }
+}
@@ -1,5 +1,5 @@
 use glium::backend::{Context, Facade};
-use glium::texture::Texture1d;
+use glium::texture::Texture2d;
 use std::cell::RefCell;
 use std::rc::Rc;
 use types::Vector3;
@@ -12,8 +12,10 @@ pub struct Lighting {
     context: Rc<Context>,
     /// Collection of the directional lights
     directional_lights: Vec<DirectionalLight>,
-    /// Texture representing the positions of the directional lights
-    pub directional_tex: RefCell<Texture1d>,
+    /// Texture representing the positions of the directional lights, one texel per light
+    pub directional_tex: RefCell<Texture2d>,
+    /// Texture representing the colours of the directional lights, one texel per light
+    pub directional_color_tex: RefCell<Texture2d>,
 }
 
 /// Struct for defining a directional light
@@ -25,6 +27,8 @@ pub struct DirectionalLight {
     pub name: String,
     /// The direction the light is facing
     pub dir: Vector3,
+    /// The colour of the light
+    pub color: Vector3,
     /// Whether the light is active
     pub active: bool,
 }
@@ -34,6 +38,7 @@ impl Default for DirectionalLight {
         DirectionalLight {
             name: "light".into(),
             dir: (1f32, 1f32, 1f32),
+            color: (1f32, 1f32, 1f32),
             active: true,
         }
     }
@@ -46,13 +51,15 @@ impl Lighting {
         F: Facade + Clone,
     {
         let context = facade.get_context().clone();
-        let dir_lights: Vec<Vector3> = Vec::new();
-        let dir_tex = Texture1d::new(&context, dir_lights.as_slice()).unwrap();
+        let dir_lights: Vec<Vec<Vector3>> = vec![Vec::new()];
+        let dir_tex = Texture2d::new(&context, dir_lights.clone()).unwrap();
+        let color_tex = Texture2d::new(&context, dir_lights).unwrap();
 
         Lighting {
             context,
             directional_lights: Vec::new(),
             directional_tex: RefCell::new(dir_tex),
+            directional_color_tex: RefCell::new(color_tex),
         }
     }
 
@@ -71,6 +78,7 @@ impl Lighting {
     /// Note: must be called when mutating any lighting data
     pub fn regenerate_lighting_tex(&mut self) {
         let mut dir_tex = self.directional_tex.borrow_mut();
+        let mut color_tex = self.directional_color_tex.borrow_mut();
 
         let lights = self
             .directional_lights
@@ -78,9 +86,16 @@ impl Lighting {
             .filter(|d| d.active)
             .map(|d| d.dir)
             .collect::<Vec<Vector3>>();
+        let colors = self
+            .directional_lights
+            .par_iter()
+            .filter(|d| d.active)
+            .map(|d| d.color)
+            .collect::<Vec<Vector3>>();
 
-        let dir_tex_1d = Texture1d::new(&self.context, lights.as_slice()).unwrap();
-        *dir_tex = dir_tex_1d;
+        // a row of texels as GLES has no 1D textures
+        *dir_tex = Texture2d::new(&self.context, vec![lights]).unwrap();
+        *color_tex = Texture2d::new(&self.context, vec![colors]).unwrap();
     }
 
     /// Get a ref to a directional light
@@ -88,6 +103,30 @@ impl Lighting {
         &mut self.directional_lights[index]
     }
 
+    /// Get a ref to the first directional light, which the sky drives as the sun
+    pub fn get_primary_directional_light(&mut self) -> Option<&mut DirectionalLight> {
+        self.directional_lights.first_mut()
+    }
+
+    /// Point the first directional light along dir with color, adding a "sun" light if there
+    /// are none, the lighting textures are only regenerated when the light changes
+    pub fn set_primary_directional_light(&mut self, dir: Vector3, color: Vector3) {
+        if self.directional_lights.is_empty() {
+            self.add_directional_light("sun".to_string(), dir);
+        }
+
+        let changed = {
+            let light = &mut self.directional_lights[0];
+            let changed = light.dir != dir || light.color != color;
+            light.dir = dir;
+            light.color = color;
+            changed
+        };
+        if changed {
+            self.regenerate_lighting_tex();
+        }
+    }
+
     /// Get a ref to a directional light from its name, returning the first found
     pub fn get_directional_light_by_name(&mut self, name: &str) -> Option<&mut DirectionalLight> {
         for i in 0..self.directional_lights.len() {
@@ -0,0 +1,288 @@
+use glium::backend::Facade;
+use glium::texture::{MipmapsOption, RawImage2d, Texture2d, UncompressedFloatFormat};
+use image;
+use image::DynamicImage;
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::SplitWhitespace;
+
+/// A 3D colour lookup table, stored as a strip of size slices of size x size texels
+/// with red increasing across each slice, green increasing up it and blue increasing per slice
+pub struct Lut {
+    /// The entries and input domain of the table
+    pub data: CubeData,
+    /// The texture the table is stored in
+    pub texture: Texture2d,
+}
+
+impl Lut {
+    /// Creates a lookup table that leaves colours unchanged, a starting point for grading
+    pub fn identity<F: Facade>(facade: &F, size: u32) -> Result<Lut, String> {
+        Lut::from_data(facade, CubeData::identity(size))
+    }
+
+    /// Creates a lookup table from an image strip of size slices each size x size pixels,
+    /// with green increasing down each slice as exported by most grading tools
+    pub fn from_strip<F: Facade>(facade: &F, strip: &DynamicImage) -> Result<Lut, String> {
+        let strip = strip.to_rgb();
+        let (width, height) = strip.dimensions();
+        if width != height * height {
+            return Err(format!(
+                "LUT strip is {}x{}, the width should be the height squared",
+                width, height
+            ));
+        }
+
+        // the image rows go top to bottom, so the first row becomes the bottom of the texture
+        let entries = strip
+            .into_raw()
+            .into_iter()
+            .map(|c| f32::from(c) / 255f32)
+            .collect::<Vec<_>>();
+
+        Lut::from_data(
+            facade,
+            CubeData {
+                size: height,
+                domain_min: [0f32; 3],
+                domain_max: [1f32; 3],
+                entries,
+            },
+        )
+    }
+
+    /// Loads a lookup table from an image strip file
+    pub fn load_strip<F: Facade, P: AsRef<Path>>(facade: &F, path: P) -> Result<Lut, String> {
+        let strip = image::open(path.as_ref())
+            .map_err(|e| format!("Could not load LUT {}: {}", path.as_ref().display(), e))?;
+        Lut::from_strip(facade, &strip)
+    }
+
+    /// Creates a lookup table from the contents of a .cube file
+    pub fn from_cube<F: Facade>(facade: &F, source: &str) -> Result<Lut, String> {
+        Lut::from_data(facade, parse_cube(source)?)
+    }
+
+    /// Loads a lookup table from a .cube file
+    pub fn load_cube<F: Facade, P: AsRef<Path>>(facade: &F, path: P) -> Result<Lut, String> {
+        let mut source = String::new();
+        File::open(path.as_ref())
+            .and_then(|mut f| f.read_to_string(&mut source))
+            .map_err(|e| format!("Could not load LUT {}: {}", path.as_ref().display(), e))?;
+        Lut::from_cube(facade, &source)
+    }
+
+    /// Uploads the table entries to a texture
+    pub fn from_data<F: Facade>(facade: &F, data: CubeData) -> Result<Lut, String> {
+        let size = data.size;
+        let image = RawImage2d::from_raw_rgb(data.entries.clone(), (size * size, size));
+        let texture = Texture2d::with_format(
+            facade,
+            image,
+            UncompressedFloatFormat::F16F16F16,
+            MipmapsOption::NoMipmap,
+        )
+        .map_err(|e| format!("Could not create LUT texture: {:?}", e))?;
+
+        Ok(Lut { data, texture })
+    }
+}
+
+/// The entries of a lookup table and the range of input colours they cover
+#[derive(Clone, PartialEq, Debug)]
+pub struct CubeData {
+    /// The number of entries along each colour axis
+    pub size: u32,
+    /// The input colour mapped by the first entry on each axis
+    pub domain_min: [f32; 3],
+    /// The input colour mapped by the last entry on each axis
+    pub domain_max: [f32; 3],
+    /// The rgb output colours in strip order, rows of size * size entries from green 0 upwards
+    pub entries: Vec<f32>,
+}
+
+impl CubeData {
+    /// A table of size entries per axis that leaves colours unchanged
+    pub fn identity(size: u32) -> CubeData {
+        let step = 1f32 / (size.max(2) - 1) as f32;
+        let mut entries = Vec::with_capacity((size * size * size) as usize * 3);
+        for g in 0..size {
+            for b in 0..size {
+                for r in 0..size {
+                    entries.push(r as f32 * step);
+                    entries.push(g as f32 * step);
+                    entries.push(b as f32 * step);
+                }
+            }
+        }
+
+        CubeData {
+            size,
+            domain_min: [0f32; 3],
+            domain_max: [1f32; 3],
+            entries,
+        }
+    }
+
+    /// The entry at the red, green and blue indices
+    pub fn entry(&self, r: u32, g: u32, b: u32) -> [f32; 3] {
+        let index = ((g * self.size * self.size + b * self.size + r) * 3) as usize;
+        [
+            self.entries[index],
+            self.entries[index + 1],
+            self.entries[index + 2],
+        ]
+    }
+
+    /// Looks up a colour, interpolating between the surrounding entries
+    pub fn sample(&self, color: [f32; 3]) -> [f32; 3] {
+        let last = (self.size.max(1) - 1) as f32;
+        let mut lower = [0u32; 3];
+        let mut upper = [0u32; 3];
+        let mut frac = [0f32; 3];
+        for (c, value) in color.iter().enumerate() {
+            let range = self.domain_max[c] - self.domain_min[c];
+            let pos = ((value - self.domain_min[c]) / range).max(0f32).min(1f32) * last;
+            lower[c] = pos.floor() as u32;
+            upper[c] = (lower[c] + 1).min(last as u32);
+            frac[c] = pos - pos.floor();
+        }
+
+        let mut out = [0f32; 3];
+        for corner in 0..8 {
+            let pick = |c: usize| corner & (1 << c) != 0;
+            let mut weight = 1f32;
+            let mut index = [0u32; 3];
+            for c in 0..3 {
+                index[c] = if pick(c) { upper[c] } else { lower[c] };
+                weight *= if pick(c) { frac[c] } else { 1f32 - frac[c] };
+            }
+            let entry = self.entry(index[0], index[1], index[2]);
+            for (out, entry) in out.iter_mut().zip(entry.iter()) {
+                *out += entry * weight;
+            }
+        }
+        out
+    }
+
+    /// Blends from the colours a grades to to the colours b grades to by amount,
+    /// giving a table the size and domain of b
+    pub fn mix(a: &CubeData, b: &CubeData, amount: f32) -> CubeData {
+        let last = (b.size.max(2) - 1) as f32;
+        let mut entries = b.entries.clone();
+        for g in 0..b.size {
+            for bl in 0..b.size {
+                for r in 0..b.size {
+                    // the input colour this entry of b is for
+                    let mut input = [0f32; 3];
+                    for (c, index) in [r, g, bl].iter().enumerate() {
+                        input[c] = b.domain_min[c]
+                            + (b.domain_max[c] - b.domain_min[c]) * *index as f32 / last;
+                    }
+                    let from = a.sample(input);
+                    let index = ((g * b.size * b.size + bl * b.size + r) * 3) as usize;
+                    for (entry, from) in entries[index..index + 3].iter_mut().zip(from.iter()) {
+                        *entry = from + (*entry - from) * amount;
+                    }
+                }
+            }
+        }
+
+        CubeData {
+            size: b.size,
+            domain_min: b.domain_min,
+            domain_max: b.domain_max,
+            entries,
+        }
+    }
+}
+
+/// Parses the contents of a .cube file
+pub fn parse_cube(source: &str) -> Result<CubeData, String> {
+    let mut size = None;
+    let mut domain_min = [0f32; 3];
+    let mut domain_max = [1f32; 3];
+    let mut entries = Vec::new();
+
+    for (line_num, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let keyword = parts.next().unwrap_or("");
+        let parse_err = || format!("Invalid .cube line {}: {}", line_num + 1, line);
+
+        match keyword {
+            "TITLE" => {}
+            "LUT_1D_SIZE" => return Err("1D .cube LUTs are not supported".into()),
+            "LUT_3D_SIZE" => {
+                size = Some(
+                    parts
+                        .next()
+                        .and_then(|p| p.parse::<u32>().ok())
+                        .filter(|s| *s >= 2)
+                        .ok_or_else(parse_err)?,
+                );
+            }
+            "LUT_3D_INPUT_RANGE" => {
+                let min = parts.next().and_then(|p| p.parse::<f32>().ok());
+                let max = parts.next().and_then(|p| p.parse::<f32>().ok());
+                match (min, max) {
+                    (Some(min), Some(max)) => {
+                        domain_min = [min; 3];
+                        domain_max = [max; 3];
+                    }
+                    _ => return Err(parse_err()),
+                }
+            }
+            "DOMAIN_MIN" => domain_min = parse_vec3(parts).ok_or_else(parse_err)?,
+            "DOMAIN_MAX" => domain_max = parse_vec3(parts).ok_or_else(parse_err)?,
+            _ => entries.push(parse_vec3(line.split_whitespace()).ok_or_else(parse_err)?),
+        }
+    }
+
+    let size = size.ok_or_else(|| "No LUT_3D_SIZE in .cube".to_string())?;
+    if entries.len() != (size * size * size) as usize {
+        return Err(format!(
+            "Expected {} .cube entries for size {}, found {}",
+            size * size * size,
+            size,
+            entries.len()
+        ));
+    }
+    if (0..3).any(|c| domain_max[c] <= domain_min[c]) {
+        return Err(format!(
+            "The .cube domain max {:?} should be greater than the min {:?}",
+            domain_max, domain_min
+        ));
+    }
+
+    // entries have red changing fastest then green then blue, rearrange into the strip
+    let mut data = vec![0f32; entries.len() * 3];
+    for (i, entry) in entries.iter().enumerate() {
+        let i = i as u32;
+        let (r, g, b) = (i % size, (i / size) % size, i / (size * size));
+        let index = ((g * size * size + b * size + r) * 3) as usize;
+        data[index..index + 3].copy_from_slice(entry);
+    }
+
+    Ok(CubeData {
+        size,
+        domain_min,
+        domain_max,
+        entries: data,
+    })
+}
+
+/// Parses the next three values as floats
+fn parse_vec3(mut parts: SplitWhitespace) -> Option<[f32; 3]> {
+    let mut vals = [0f32; 3];
+    for val in &mut vals {
+        *val = parts.next()?.parse().ok()?;
+    }
+    Some(vals)
+}
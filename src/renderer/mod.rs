@@ -1,22 +1,36 @@
 /// Module for utility functions for textures
 #[macro_use]
 pub mod texture;
+/// Distance fog settings for the built in shaders
+pub mod fog;
 /// Module for the lighting system
 pub mod lighting;
+/// Colour lookup tables for grading
+pub mod lut;
 /// Rendering post processing effects
 pub mod posteffect;
 /// Module for dealing with shaders
 pub mod shader;
+/// Procedural sky and time of day
+pub mod sky;
+/// Cubemap skyboxes and environment maps
+pub mod skybox;
+/// Screen transitions between game states
+pub mod transition;
 
 use glium::backend::Facade;
-use glium::draw_parameters::{BackfaceCullingMode, DepthClamp};
+use glium::draw_parameters::{
+    BackfaceCullingMode, BlendingFunction, DepthClamp, LinearBlendingFactor,
+};
+use glium::framebuffer::SimpleFrameBuffer;
 use glium::glutin::{Api, ContextBuilder, EventsLoop, GlRequest, WindowBuilder};
 use glium::index::{NoIndices, PrimitiveType};
+use glium::texture::compressed_srgb_texture2d::CompressedSrgbTexture2d;
 use glium::texture::RawImage2d;
 use glium::vertex::VertexBuffer;
-use glium::DepthTest::IfLess;
+use glium::DepthTest::{IfLess, Overwrite};
 use glium::Frame;
-use glium::{Blend, Depth, Display, DrawParameters, Surface};
+use glium::{Blend, Depth, Display, DrawParameters, Rect, Surface};
 
 use glium_text;
 use glium_text::{FontTexture, TextDisplay, TextSystem};
@@ -34,19 +48,30 @@ use image;
 
 use rayon::prelude::*;
 
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fs::File;
 use std::fs::OpenOptions;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
+use fog::{Fog, FogMode};
 use input::{Input, MouseButton};
 use lighting::Lighting;
-use posteffect::{render_to_texture, PostEffect};
+use posteffect::{
+    render_post_passes, render_to_texture, ColorBlindDeficiency, ColorBlindMode, PostEffect,
+    PostHistory, SceneTextures, ToneMapOperator,
+};
 use shader::Shaders;
-use types::{Camera, PhysicsType, RenderItem, ShaderIn, TextItem};
+use sky::Sky;
+use skybox::Skybox;
+use types::{
+    BlendMode, Camera, CullMode, Material, Matrix4, OrientationType, PhysicsType, PrevShaderIn,
+    ProjectionType, RenderItem, ShaderIn, TextItem, Topology, Transform, Vector3,
+};
 use utils::{
-    build_fp_view_matrix, build_persp_proj_mat, frustrum_test, get_frustum_planes, mul_mat4,
+    build_fp_view_matrix, build_proj_mat, frustrum_test, get_cam_frustum_planes, jitter_proj_mat,
 };
 
 /// struct for abstracting the render state
@@ -63,10 +88,31 @@ pub struct Renderer {
     imgui_rend: ImGuiRenderer,
     /// Instance of PostEffect used for rendering post processing
     pub post_effect: PostEffect,
+    /// The post pass outputs kept from the last frame for each camera
+    post_histories: Vec<PostHistory>,
+    /// The time the last frame was drawn
+    last_draw_time: f64,
+    /// The instance transforms of each render item last frame by its index, kept for the
+    /// velocity buffer
+    prev_transforms: Vec<Vec<Transform>>,
+    /// The problem with the material of each render item by its index, shown in the editor,
+    /// these items are drawn with the missing shader and texture
+    pub material_warnings: HashMap<usize, String>,
+    /// The material and topology of each render item by its index when it was last validated
+    validated_materials: Vec<(Material, Topology)>,
     /// The shaders that can be used for rendering
     pub shaders: Shaders,
     /// The lighting system
     pub lighting: Lighting,
+    /// The distance fog applied by the built in shaders
+    pub fog: Fog,
+    /// The procedural sky drawn instead of the skybox, which drives the first directional light,
+    /// adding one if there are none, and the ambient and fog colours from the time of day
+    pub sky: Option<Sky>,
+    /// The skybox drawn behind the scene and passed to the shaders as env_map
+    pub skybox: Option<Skybox>,
+    /// Black environment map passed to the shaders when there is no skybox
+    empty_env_map: Skybox,
     /// Info on the current gif being written to
     gif_info: Option<GifInfo>,
     /// stuct to track the fps
@@ -81,6 +127,24 @@ pub struct Renderer {
     pub show_editor: bool,
 }
 
+/// The result of rendering the scene for a single camera
+struct CamPass {
+    /// The index of the camera, which its post history is kept at
+    index: usize,
+    /// The textures the scene was rendered to
+    scene: SceneTextures,
+    /// The pixel rect on screen the camera is composited to
+    viewport: Rect,
+    /// The camera position
+    cam_pos: Vector3,
+    /// The projection matrix used for the camera
+    projection_matrix: Matrix4,
+    /// The modelview matrix used for the camera
+    modelview_matrix: Matrix4,
+    /// The projection matrix before any jitter was applied
+    base_projection_matrix: Matrix4,
+}
+
 struct GifInfo {
     /// The encoder for the current gif
     encoder: Arc<Mutex<gif::Encoder<File>>>,
@@ -94,11 +158,31 @@ impl Renderer {
         let window_builder = WindowBuilder::new()
             .with_title(title)
             .with_fullscreen(Some(events_loop.get_primary_monitor()));
-        let ctx_builder = ContextBuilder::new()
-            .with_depth_buffer(24)
-            .with_vsync(true)
-            .with_gl(GlRequest::Specific(Api::OpenGl, (4, 0)));
-        let display = Display::new(window_builder, ctx_builder, &events_loop).unwrap();
+        let ctx_builder = |api, version| {
+            ContextBuilder::new()
+                .with_depth_buffer(24)
+                .with_srgb(true)
+                .with_vsync(true)
+                .with_gl(GlRequest::Specific(api, version))
+        };
+        // GL 4.0 for tessellation, falling back to 3.3 without it and then GLES 3.0
+        let requests = [
+            (Api::OpenGl, (4, 0)),
+            (Api::OpenGl, (3, 3)),
+            (Api::OpenGlEs, (3, 0)),
+        ];
+        let display = requests
+            .iter()
+            .filter_map(|&(api, version)| {
+                Display::new(
+                    window_builder.clone(),
+                    ctx_builder(api, version),
+                    &events_loop,
+                )
+                .ok()
+            })
+            .next()
+            .expect("Could not create a GL 3.3 or GLES 3.0 context");
 
         // create a text system instance and font
         let text_system = TextSystem::new(&display);
@@ -155,6 +239,7 @@ impl Renderer {
         let shaders = Shaders::new(&display);
         let post_effect = PostEffect::new(&display);
         let lighting = Lighting::new(&display);
+        let empty_env_map = Skybox::solid(&display, (0f32, 0f32, 0f32)).unwrap();
 
         let fps_counter = FPSCounter::new();
 
@@ -165,12 +250,21 @@ impl Renderer {
             imgui,
             imgui_rend,
             post_effect,
+            post_histories: Vec::new(),
+            last_draw_time: time::precise_time_s(),
+            prev_transforms: Vec::new(),
+            material_warnings: HashMap::new(),
+            validated_materials: Vec::new(),
             start_time: time::precise_time_s(),
             shaders,
             fps_counter,
             fps: 0f32,
             gif_info: None,
             lighting,
+            fog: Fog::default(),
+            sky: None,
+            skybox: None,
+            empty_env_map,
             render_count: 0usize,
             show_editor: false,
         };
@@ -264,6 +358,118 @@ impl Renderer {
             });
         }
     }
+
+    /// Returns the named texture, or the missing checkerboard if there is none
+    fn texture_or_missing(&self, name: &str) -> &CompressedSrgbTexture2d {
+        self.shaders
+            .textures
+            .get(name)
+            .unwrap_or(&self.shaders.textures["missing"])
+    }
+
+    /// The last frame's transforms of an item's visible instances, in the order they are drawn
+    fn prev_instances<T: Default>(
+        &self,
+        item_index: usize,
+        item: &RenderItem<T>,
+        frustum_planes: &[(f32, f32, f32, f32)],
+    ) -> Vec<PrevShaderIn> {
+        // instances without a transform last frame have not moved
+        let prev_transforms = self.prev_transforms.get(item_index);
+        item.instance_transforms
+            .iter()
+            .enumerate()
+            .filter(|&(_, t)| instance_visible(t, frustum_planes))
+            .map(|(i, t)| {
+                let prev = prev_transforms.and_then(|p| p.get(i)).unwrap_or(t);
+                PrevShaderIn {
+                    prev_world_position: prev.pos,
+                    prev_world_rotation: prev.rot,
+                    prev_world_scale: prev.scale,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Converts a normalised viewport into a pixel rect for the given dimensions
+fn viewport_rect(viewport: (f32, f32, f32, f32), dimensions: (u32, u32)) -> Rect {
+    let (width, height) = (dimensions.0 as f32, dimensions.1 as f32);
+    Rect {
+        left: (viewport.0 * width) as u32,
+        bottom: (viewport.1 * height) as u32,
+        width: (viewport.2 * width) as u32,
+        height: (viewport.3 * height) as u32,
+    }
+}
+
+/// Whether an instance is active and inside the frustum, or not frustum culled
+fn instance_visible(t: &Transform, frustum_planes: &[(f32, f32, f32, f32)]) -> bool {
+    (!t.cull
+        || frustrum_test(
+            &t.pos,
+            t.scale.0.max(t.scale.1.max(t.scale.2)) * 2.5f32,
+            frustum_planes,
+        ))
+        && t.active
+}
+
+/// Returns the draw parameters for the blend, depth and cull state of a material,
+/// depth clamping is left off for GLES which does not support it
+fn material_params(material: &Material, gles: bool) -> DrawParameters<'static> {
+    DrawParameters {
+        depth: Depth {
+            test: if material.depth_test {
+                IfLess
+            } else {
+                Overwrite
+            },
+            write: material.depth_write,
+            clamp: if gles {
+                DepthClamp::NoClamp
+            } else {
+                DepthClamp::Clamp
+            },
+            ..Default::default()
+        },
+        blend: match material.blend_mode {
+            BlendMode::Opaque => Blend::default(),
+            BlendMode::Alpha => Blend::alpha_blending(),
+            BlendMode::Additive => Blend {
+                color: BlendingFunction::Addition {
+                    source: LinearBlendingFactor::SourceAlpha,
+                    destination: LinearBlendingFactor::One,
+                },
+                alpha: BlendingFunction::Addition {
+                    source: LinearBlendingFactor::Zero,
+                    destination: LinearBlendingFactor::One,
+                },
+                constant_value: (0.0, 0.0, 0.0, 0.0),
+            },
+        },
+        backface_culling: match material.cull_mode {
+            CullMode::None => BackfaceCullingMode::CullingDisabled,
+            CullMode::Clockwise => BackfaceCullingMode::CullClockwise,
+            CullMode::CounterClockwise => BackfaceCullingMode::CullCounterClockwise,
+        },
+        ..Default::default()
+    }
+}
+
+/// Returns the primitive type drawn without tessellation for a topology
+fn primitive_type(topology: Topology) -> PrimitiveType {
+    match topology {
+        Topology::TrianglesList => PrimitiveType::TrianglesList,
+        Topology::LinesList => PrimitiveType::LinesList,
+        Topology::LineStrip => PrimitiveType::LineStrip,
+        Topology::Points => PrimitiveType::Points,
+    }
+}
+
+/// Returns the squared distance between two points
+fn dist_sq(a: Vector3, b: Vector3) -> f32 {
+    let d = (a.0 - b.0, a.1 - b.1, a.2 - b.2);
+    d.0 * d.0 + d.1 * d.1 + d.2 * d.2
 }
 
 /// Trait for drawing to screen
@@ -305,6 +511,16 @@ impl Draw for Renderer {
         text_items: &mut Vec<TextItem>,
         f: F,
     ) {
+        // pick up any shader files that have changed
+        self.shaders.reload(&self.display);
+
+        // move the sun and light the scene with it
+        if let Some(ref mut sky) = self.sky {
+            sky.update((time::precise_time_s() - self.last_draw_time) as f32);
+            self.lighting
+                .set_primary_directional_light(sky.sun_dir(), sky.sun_color());
+        }
+
         let target = Arc::new(Mutex::new(self.display.draw()));
 
         self.draw_render_items(Arc::clone(&target), cams, render_items);
@@ -327,225 +543,393 @@ impl Draw for Renderer {
         cams: &mut Vec<Camera>,
         render_items: &mut Vec<RenderItem<T>>,
     ) {
-        // draw parameters
-        let params = DrawParameters {
-            depth: Depth {
-                test: IfLess,
-                write: true,
-                clamp: DepthClamp::Clamp,
-                ..Default::default()
-            },
-            blend: Blend::alpha_blending(),
-            backface_culling: BackfaceCullingMode::CullClockwise,
-            ..Default::default()
-        };
+        // forget removed items
+        self.validated_materials.truncate(render_items.len());
+        self.material_warnings
+            .retain(|&index, _| index < render_items.len());
+
+        // check materials missing shaders or textures when they change, they are drawn as missing
+        for (index, item) in render_items.iter().enumerate() {
+            let unchanged = self
+                .validated_materials
+                .get(index)
+                .map_or(false, |&(ref material, topology)| {
+                    *material == item.material && topology == item.topology
+                });
+            if unchanged {
+                continue;
+            }
+
+            let result = self
+                .shaders
+                .validate_material(&item.material, item.topology);
+            match result {
+                Ok(_) => self.material_warnings.remove(&index),
+                Err(e) => self.material_warnings.insert(index, e),
+            };
+
+            let validated = (item.material.clone(), item.topology);
+            if index < self.validated_materials.len() {
+                self.validated_materials[index] = validated;
+            } else {
+                self.validated_materials.push(validated);
+            }
+        }
 
         // drawing a frame
         let context = self.display.get_context().clone();
         let (width, height) = self.display.get_framebuffer_dimensions();
         let mut render_count = 0usize;
-        let mut cols = Vec::new();
-        let mut depths = Vec::new();
-        let mut p_mat = None;
-        let mut mv_mat = None;
+        let mut cam_passes = Vec::new();
 
-        cams.iter_mut().for_each(|cam| {
-            // uniforms passed to the shaders
-            let projection_matrix =
-                build_persp_proj_mat(60f32, width as f32 / height as f32, 0.01f32, 1000f32);
-            if p_mat.is_none() {
-                p_mat = Some(projection_matrix);
+        // keep a post history for each camera
+        self.post_histories.truncate(cams.len());
+        while self.post_histories.len() < cams.len() {
+            self.post_histories.push(PostHistory::default());
+        }
+
+        // the ambient light and the colour distant objects fade to
+        let (ambient_color, fog_color) = match self.sky {
+            Some(ref sky) => (sky.ambient_color(), sky.fog_color()),
+            None => ((1f32, 1f32, 1f32), self.fog.color),
+        };
+        let fog = &self.fog;
+
+        for (index, cam) in cams.iter_mut().enumerate() {
+            // the area of the screen this camera is drawn to
+            let viewport = viewport_rect(cam.viewport, (width, height));
+            if viewport.width == 0 || viewport.height == 0 {
+                continue;
             }
+
+            // uniforms passed to the shaders
+            let aspect = viewport.width as f32 / viewport.height as f32;
+            let base_projection_matrix = build_proj_mat(&cam, aspect);
+            // sub-pixel jitter for temporal anti-aliasing
+            let jitter = self.post_effect.taa_jitter(
+                self.post_histories[index].frame,
+                (viewport.width, viewport.height),
+            );
+            let projection_matrix = jitter_proj_mat(base_projection_matrix, jitter);
             let modelview_matrix = build_fp_view_matrix(&cam);
-            if mv_mat.is_none() {
-                mv_mat = Some(modelview_matrix);
-            }
             let cam_pos = cam.pos;
             let time = (time::precise_time_s() - self.start_time) as f32;
 
-            // calc frustum places for culling
-            let combo_matrix = mul_mat4(projection_matrix, modelview_matrix);
-            let frustum_planes = get_frustum_planes(&combo_matrix);
-
-            // render to texture/depth
-            let mut target = target.lock().unwrap();
-            let (target_color, target_depth) =
-                render_to_texture(&self.post_effect, &context, &mut *target, |target| {
-                    // clear the colour and depth buffers
-                    target.clear_color_and_depth((1.0, 1.0, 1.0, 1.0), 1.0);
-
-                    // drawing the render items (with more than one instance)
-                    render_items
-                        .iter()
-                        .filter(|r| r.active && !r.instance_transforms.is_empty())
-                        .for_each(|item| {
-                            // building the vertex and index buffers
-                            let vertex_buffer =
-                                VertexBuffer::new(&self.display, &item.vertices).unwrap();
-
-                            // add positions for instances
-                            let per_instance = {
-                                let data = item
-                                    .instance_transforms
-                                    .par_iter()
-                                    .filter(|t| {
-                                        (!t.cull
-                                            || frustrum_test(
-                                                &t.pos,
-                                                t.scale.0.max(t.scale.1.max(t.scale.2)) * 2.5f32,
-                                                &frustum_planes,
-                                            )) && t.active
-                                    })
-                                    .map(|t| ShaderIn {
-                                        world_position: t.pos,
-                                        world_rotation: t.rot,
-                                        world_scale: t.scale,
-                                    })
-                                    .collect::<Vec<_>>();
-
-                                // if there are no active transforms skip ri
-                                if data.is_empty() {
-                                    return;
-                                }
+            // calc frustum planes for culling, the jitter is too small to matter
+            let frustum_planes = get_cam_frustum_planes(&cam, aspect);
+
+            // render to the texture/depth kept from the last frame
+            let (target_color, target_depth) = self.post_histories[index]
+                .take_scene_targets(&self.post_effect, (viewport.width, viewport.height));
+
+            // per-pixel velocity from the last frame's camera and instance transforms,
+            // drawn alongside the scene
+            let velocity_targets = if self.post_effect.velocity_active() {
+                let dimensions = target_color.dimensions();
+                let history = &mut self.post_histories[index];
+                Some((
+                    history.take_target(&self.post_effect, "velocity", dimensions, true),
+                    history.take_depth_target(&self.post_effect, "velocity", dimensions),
+                ))
+            } else {
+                None
+            };
+            let (prev_projection_matrix, prev_modelview_matrix) = {
+                let history = &self.post_histories[index];
+                (
+                    history.projection_matrix.unwrap_or(base_projection_matrix),
+                    history.modelview_matrix.unwrap_or(modelview_matrix),
+                )
+            };
+
+            render_to_texture(&context, &target_color, &target_depth, |target| {
+                // clear the colour and depth buffers
+                target.clear_color_and_depth((1.0, 1.0, 1.0, 1.0), 1.0);
+
+                let mut velocity_target = velocity_targets.as_ref().map(|targets| {
+                    let mut velocity_target =
+                        SimpleFrameBuffer::with_depth_buffer(&self.display, &targets.0, &targets.1)
+                            .unwrap();
+                    velocity_target.clear_color_and_depth((0.0, 0.0, 0.0, 0.0), 1.0);
+                    velocity_target
+                });
+
+                // the sky or skybox is drawn at the far plane without writing depth
+                if let Some(ref sky) = self.sky {
+                    let sky_uniforms = uniform! {
+                        projection_matrix: projection_matrix,
+                        modelview_matrix: modelview_matrix,
+                        sun_dir: sky.sun_dir(),
+                        sun_color: sky.sun_color(),
+                        fog_color: fog_color,
+                        sky_turbidity: sky.turbidity,
+                        sky_intensity: sky.intensity,
+                    };
+                    target
+                        .draw(
+                            &self.post_effect.vertex_buffer,
+                            &self.post_effect.index_buffer,
+                            self.shaders.sky_program(),
+                            &sky_uniforms,
+                            &Default::default(),
+                        )
+                        .unwrap();
+                } else if let Some(ref skybox) = self.skybox {
+                    let skybox_uniforms = uniform! {
+                        projection_matrix: projection_matrix,
+                        modelview_matrix: modelview_matrix,
+                        env_map: &skybox.cubemap,
+                        skybox_intensity: skybox.intensity,
+                    };
+                    target
+                        .draw(
+                            &self.post_effect.vertex_buffer,
+                            &self.post_effect.index_buffer,
+                            self.shaders.skybox_program(),
+                            &skybox_uniforms,
+                            &Default::default(),
+                        )
+                        .unwrap();
+                }
+
+                // gather the visible instances of the render items
+                let mut opaque = Vec::new();
+                let mut transparent = Vec::new();
+                render_items
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, r)| r.active && !r.instance_transforms.is_empty())
+                    .for_each(|(item_index, item)| {
+                        let mut data = item
+                            .instance_transforms
+                            .par_iter()
+                            .filter(|t| instance_visible(t, &frustum_planes))
+                            .map(|t| ShaderIn {
+                                world_position: t.pos,
+                                world_rotation: t.rot,
+                                world_scale: t.scale,
+                            })
+                            .collect::<Vec<_>>();
+
+                        // if there are no active transforms skip ri
+                        if data.is_empty() {
+                            return;
+                        }
+
+                        // add instances to render_count
+                        render_count += data.len();
 
-                                // add instances to render_count
-                                render_count += data.len();
-
-                                VertexBuffer::dynamic(&self.display, &data).unwrap()
-                            };
-
-                            let tex_name = item
-                                .material
-                                .texture_name
-                                .clone()
-                                .unwrap_or_else(|| "default".to_string());
-                            let normal_tex_name = item
-                                .material
-                                .normal_texture_name
-                                .clone()
-                                .unwrap_or_else(|| "default_normal".to_string());
-
-                            let dir_lights = self.lighting.directional_tex.borrow();
-
-                            let uniforms = uniform! {
-                                projection_matrix: projection_matrix,
-                                modelview_matrix: modelview_matrix,
-                                cam_pos: cam_pos,
-                                viewport: (width as f32, height as f32),
-                                time: time,
-                                tex: &self.shaders.textures[tex_name.as_str()],
-                                normal_tex: &self.shaders.textures[normal_tex_name.as_str()],
-                                dir_lights: &*dir_lights,
-                            };
-
-                            target
+                        if item.material.is_transparent() {
+                            // instances back to front, the item sorted by its furthest
+                            data.sort_by(|a, b| {
+                                dist_sq(b.world_position, cam_pos)
+                                    .partial_cmp(&dist_sq(a.world_position, cam_pos))
+                                    .unwrap_or(Ordering::Equal)
+                            });
+                            let dist = dist_sq(data[0].world_position, cam_pos);
+                            transparent.push((dist, item_index, item, data));
+                        } else {
+                            opaque.push((item_index, item, data));
+                        }
+                    });
+                transparent.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+
+                // drawing opaque items first then transparent items back to front
+                opaque
+                    .into_iter()
+                    .chain(
+                        transparent
+                            .into_iter()
+                            .map(|(_, item_index, item, data)| (item_index, item, data)),
+                    )
+                    .for_each(|(item_index, item, data)| {
+                        // building the vertex and index buffers
+                        let vertex_buffer =
+                            VertexBuffer::new(&self.display, &item.vertices).unwrap();
+                        let per_instance = VertexBuffer::dynamic(&self.display, &data).unwrap();
+
+                        let tex_name = item
+                            .material
+                            .texture_name
+                            .clone()
+                            .unwrap_or_else(|| "default".to_string());
+                        let normal_tex_name = item
+                            .material
+                            .normal_texture_name
+                            .clone()
+                            .unwrap_or_else(|| "default_normal".to_string());
+
+                        let tessellation = &item.material.tessellation;
+                        let displacement_tex = tessellation
+                            .displacement_texture_name
+                            .as_ref()
+                            .and_then(|name| self.shaders.data_textures.get(name));
+
+                        let dir_lights = self.lighting.directional_tex.borrow();
+                        let dir_light_colors = self.lighting.directional_color_tex.borrow();
+                        let env_map = self.skybox.as_ref().unwrap_or(&self.empty_env_map);
+
+                        let uniforms = uniform! {
+                            projection_matrix: projection_matrix,
+                            modelview_matrix: modelview_matrix,
+                            cam_pos: cam_pos,
+                            viewport: (viewport.width as f32, viewport.height as f32),
+                            time: time,
+                            tex: self.texture_or_missing(&tex_name),
+                            normal_tex: self.texture_or_missing(&normal_tex_name),
+                            dir_lights: &*dir_lights,
+                            dir_light_colors: &*dir_light_colors,
+                            ambient_color: ambient_color,
+                            fog_active: fog.active,
+                            fog_mode: fog.mode_index(),
+                            fog_color: fog_color,
+                            fog_density: fog.density,
+                            fog_start: fog.start,
+                            fog_end: fog.end,
+                            fog_height_falloff: fog.height_falloff,
+                            tess_inner_level: tessellation.inner_level,
+                            tess_outer_level: tessellation.outer_level,
+                            tess_adaptive: tessellation.adaptive,
+                            tess_near: tessellation.near_distance,
+                            tess_far: tessellation.far_distance,
+                            displacement_tex: displacement_tex
+                                .unwrap_or(&self.shaders.data_textures["flat"]),
+                            has_displacement: displacement_tex.is_some(),
+                            displacement_scale: tessellation.displacement_scale,
+                            env_map: &env_map.cubemap,
+                            has_env_map: self.skybox.is_some(),
+                        };
+
+                        // triangles go through tessellation when the context supports it and
+                        // everything else is drawn with the program for the topology
+                        let program = self
+                            .shaders
+                            .program(&item.material.shader_name, item.topology)
+                            .or_else(|| self.shaders.program("missing", item.topology))
+                            .unwrap();
+                        let primitive = match item.topology {
+                            Topology::TrianglesList if self.shaders.tessellation => {
+                                PrimitiveType::Patches {
+                                    vertices_per_patch: 3,
+                                }
+                            }
+                            _ => primitive_type(item.topology),
+                        };
+
+                        let params = material_params(&item.material, self.shaders.gles);
+                        target
+                            .draw(
+                                (&vertex_buffer, per_instance.per_instance().unwrap()),
+                                &NoIndices(primitive),
+                                program,
+                                &uniforms,
+                                &params,
+                            )
+                            .unwrap();
+
+                        // the velocity of opaque items with the same buffers, primitive and
+                        // draw parameters, through the matching program
+                        if let Some(ref mut velocity_target) = velocity_target {
+                            if item.material.is_transparent() {
+                                return;
+                            }
+
+                            let prev_data = self.prev_instances(item_index, item, &frustum_planes);
+                            let prev_per_instance =
+                                VertexBuffer::dynamic(&self.display, &prev_data).unwrap();
+                            let velocity_uniforms = uniforms
+                                .add("base_projection_matrix", base_projection_matrix)
+                                .add("prev_projection_matrix", prev_projection_matrix)
+                                .add("prev_modelview_matrix", prev_modelview_matrix);
+                            velocity_target
                                 .draw(
-                                    (&vertex_buffer, per_instance.per_instance().unwrap()),
-                                    &NoIndices(PrimitiveType::Patches {
-                                        vertices_per_patch: 3,
-                                    }),
-                                    &self.shaders.shaders[item.material.shader_name.as_str()],
-                                    &uniforms,
+                                    (
+                                        &vertex_buffer,
+                                        per_instance.per_instance().unwrap(),
+                                        prev_per_instance.per_instance().unwrap(),
+                                    ),
+                                    &NoIndices(primitive),
+                                    self.shaders.velocity_program(item.topology),
+                                    &velocity_uniforms,
                                     &params,
                                 )
                                 .unwrap();
-                        });
-                });
-
-            cols.push(target_color);
-            depths.push(target_depth);
-        });
-
-        //let texs_arr = Texture2dArray::new(&self.post_effect.context, cols).unwrap();
-        //let depths_arr = DepthTexture2dArray::new(&self.post_effect.context, depths).unwrap();
-
-        // second pass draw the post effect and composition
-        let uniforms = uniform! {
-            // general uniforms
-            tex: &cols[0],
-            depth_buf: &depths[0],
-            resolution: (width as f32, height as f32),
-            time: time::precise_time_s() as f32 - self.post_effect.start_time,
-            cam_pos: cams[0].pos,
-            projection_matrix: p_mat.unwrap(),
-            modelview_matrix: mv_mat.unwrap(),
-            downscale_factor: self.post_effect.downscale_factor,
-            // post effect param uniforms
-            chrom_offset: self.post_effect.post_shader_options.chrom_offset,
-            chrom_amt: self.post_effect.post_shader_options.chrom_amt,
-            blur: self.post_effect.post_shader_options.blur,
-            blur_amt: self.post_effect.post_shader_options.blur_amt,
-            blur_radius: self.post_effect.post_shader_options.blur_radius,
-            blur_weight: self.post_effect.post_shader_options.blur_weight,
-            bokeh: self.post_effect.post_shader_options.bokeh,
-            bokeh_focal_depth: self.post_effect.post_shader_options.bokeh_focal_depth,
-            bokeh_focal_width: self.post_effect.post_shader_options.bokeh_focal_width,
-            color_offset: self.post_effect.post_shader_options.color_offset,
-            greyscale: self.post_effect.post_shader_options.greyscale,
-            noise: self.post_effect.post_shader_options.noise,
-        };
+                        }
+                    });
+            });
 
-        let uniforms = if cols.len() > 1 {
-            uniforms
-                .add("tex_1", &cols[1])
-                .add("depth_buf_1", &depths[1])
-        } else {
-            uniforms
-                .add("tex_1", &cols[0])
-                .add("depth_buf_1", &depths[0])
-        };
+            let velocity = match velocity_targets {
+                Some((velocity, depth)) => {
+                    self.post_histories[index].keep_depth_target("velocity", depth);
+                    Some(velocity)
+                }
+                None => None,
+            };
 
-        let uniforms = if cols.len() > 2 {
-            uniforms
-                .add("tex_2", &cols[2])
-                .add("depth_buf_2", &depths[2])
-        } else {
-            uniforms
-                .add("tex_2", &cols[0])
-                .add("depth_buf_2", &depths[0])
-        };
+            cam_passes.push(CamPass {
+                index,
+                scene: SceneTextures {
+                    color: target_color,
+                    depth: target_depth,
+                    velocity,
+                },
+                viewport,
+                cam_pos,
+                projection_matrix,
+                modelview_matrix,
+                base_projection_matrix,
+            });
+        }
 
-        let uniforms = if cols.len() > 3 {
-            uniforms
-                .add("tex_3", &cols[3])
-                .add("depth_buf_3", &depths[3])
-        } else {
-            uniforms
-                .add("tex_3", &cols[0])
-                .add("depth_buf_3", &depths[0])
-        };
+        // second pass draw the post effect and composite each camera into its viewport
+        let mut target = target.lock().unwrap();
+        target.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
+
+        // time since the last frame for passes that adapt over time
+        let now = time::precise_time_s();
+        let delta = (now - self.last_draw_time) as f32;
+        self.last_draw_time = now;
+        self.post_effect.update(delta);
+
+        for pass in cam_passes {
+            let history = &mut self.post_histories[pass.index];
+            // uniforms shared by every post pass
+            let uniforms = uniform! {
+                time: now as f32 - self.post_effect.start_time,
+                delta: delta,
+                cam_pos: pass.cam_pos,
+                projection_matrix: pass.projection_matrix,
+                modelview_matrix: pass.modelview_matrix,
+                prev_projection_matrix: history
+                    .projection_matrix
+                    .unwrap_or(pass.base_projection_matrix),
+                prev_modelview_matrix: history.modelview_matrix.unwrap_or(pass.modelview_matrix),
+                downscale_factor: self.post_effect.downscale_factor,
+            };
 
-        let uniforms = if cols.len() > 4 {
-            uniforms
-                .add("tex_4", &cols[4])
-                .add("depth_buf_4", &depths[4])
-        } else {
-            uniforms
-                .add("tex_4", &cols[0])
-                .add("depth_buf_4", &depths[0])
-        };
+            render_post_passes(
+                &self.post_effect,
+                &self.shaders,
+                &mut *target,
+                pass.viewport,
+                &pass.scene,
+                &uniforms,
+                history,
+            );
+
+            history.projection_matrix = Some(pass.base_projection_matrix);
+            history.modelview_matrix = Some(pass.modelview_matrix);
+            history.keep_scene(&self.post_effect, pass.scene);
+        }
 
-        let uniforms = if cols.len() > 5 {
-            uniforms
-                .add("tex_5", &cols[5])
-                .add("depth_buf_5", &depths[5])
+        // keep the transforms for working out the velocity next frame
+        if self.post_effect.velocity_active() {
+            self.prev_transforms = render_items
+                .iter()
+                .map(|r| r.instance_transforms.clone())
+                .collect();
         } else {
-            uniforms
-                .add("tex_5", &cols[0])
-                .add("depth_buf_5", &depths[0])
-        };
-
-        {
-            let mut target = target.lock().unwrap();
-            target
-                .draw(
-                    &self.post_effect.vertex_buffer,
-                    &self.post_effect.index_buffer,
-                    &self.shaders.post_shaders[self.post_effect.current_shader],
-                    &uniforms,
-                    &Default::default(),
-                )
-                .unwrap();
+            self.prev_transforms.clear();
         }
 
         self.render_count = render_count;
@@ -606,6 +990,10 @@ impl Draw for Renderer {
         // create the engine editor
         if renderer.show_editor {
             let fps = renderer.fps;
+            let post_effect = &mut renderer.post_effect;
+            let fog = &mut renderer.fog;
+            let sky = &mut renderer.sky;
+            let skybox = &mut renderer.skybox;
             // create the editor window
             ui.window(im_str!("caper editor"))
                 .size((300.0, 200.0), ImGuiCond::FirstUseEver)
@@ -635,26 +1023,381 @@ impl Draw for Renderer {
                                 }
                                 // camera rotation
                                 if ui.collapsing_header(im_str!("rotation")).build() {
-                                    ui.input_float(im_str!("x"), &mut cam.euler_rot.0)
-                                        .step(0.1)
-                                        .step_fast(1.0)
+                                    let mut orientation_type = match cam.orientation_type {
+                                        OrientationType::Euler => 0,
+                                        OrientationType::Quaternion => 1,
+                                    };
+                                    ui.combo(
+                                        im_str!("orientation"),
+                                        &mut orientation_type,
+                                        &[im_str!("Euler"), im_str!("Quaternion")],
+                                        -1,
+                                    );
+                                    let new_orientation_type = match orientation_type {
+                                        1 => OrientationType::Quaternion,
+                                        _ => OrientationType::Euler,
+                                    };
+                                    // keep the current view when switching to quaternions
+                                    if new_orientation_type != cam.orientation_type {
+                                        cam.rot = cam.orientation();
+                                        cam.orientation_type = new_orientation_type;
+                                    }
+                                    // euler angles only orient the camera in euler mode
+                                    match cam.orientation_type {
+                                        OrientationType::Euler => {
+                                            ui.input_float(im_str!("x"), &mut cam.euler_rot.0)
+                                                .step(0.1)
+                                                .step_fast(1.0)
+                                                .build();
+                                            ui.input_float(im_str!("y"), &mut cam.euler_rot.1)
+                                                .step(0.1)
+                                                .step_fast(1.0)
+                                                .build();
+                                            ui.input_float(im_str!("z"), &mut cam.euler_rot.2)
+                                                .step(0.1)
+                                                .step_fast(1.0)
+                                                .build();
+                                        }
+                                        OrientationType::Quaternion => {
+                                            ui.text(im_str!("quaternion: {:?}", cam.rot));
+                                        }
+                                    }
+                                }
+                                // camera projection
+                                if ui.collapsing_header(im_str!("projection")).build() {
+                                    let mut projection_type = match cam.projection_type {
+                                        ProjectionType::Perspective => 0,
+                                        ProjectionType::Orthographic => 1,
+                                    };
+                                    ui.combo(
+                                        im_str!("type"),
+                                        &mut projection_type,
+                                        &[im_str!("Perspective"), im_str!("Orthographic")],
+                                        -1,
+                                    );
+                                    cam.projection_type = match projection_type {
+                                        1 => ProjectionType::Orthographic,
+                                        _ => ProjectionType::Perspective,
+                                    };
+                                    ui.input_float(im_str!("fov"), &mut cam.fov)
+                                        .step(1.0)
+                                        .step_fast(10.0)
                                         .build();
-                                    ui.input_float(im_str!("y"), &mut cam.euler_rot.1)
+                                    ui.input_float(im_str!("ortho_size"), &mut cam.ortho_size)
                                         .step(0.1)
                                         .step_fast(1.0)
                                         .build();
-                                    ui.input_float(im_str!("z"), &mut cam.euler_rot.2)
-                                        .step(0.1)
+                                    ui.input_float(im_str!("znear"), &mut cam.znear)
+                                        .step(0.01)
                                         .step_fast(1.0)
                                         .build();
+                                    ui.input_float(im_str!("zfar"), &mut cam.zfar)
+                                        .step(1.0)
+                                        .step_fast(100.0)
+                                        .build();
                                 }
                             });
                         }
                     }
+                    // post pass editor
+                    if ui.collapsing_header(im_str!("Post passes")).build() {
+                        for pass in &mut post_effect.passes {
+                            ui.tree_node(im_str!("name:{}", pass.name)).build(|| {
+                                ui.checkbox(im_str!("active"), &mut pass.active);
+                                ui.text(im_str!("shader:{}", pass.shader_name));
+                                ui.input_float(im_str!("scale"), &mut pass.scale)
+                                    .step(0.05)
+                                    .step_fast(0.25)
+                                    .build();
+                            });
+                        }
+                    }
+                    // ambient occlusion editor
+                    if ui.collapsing_header(im_str!("Ambient occlusion")).build() {
+                        ui.push_id(im_str!("ssao"));
+                        let mut ssao = post_effect.ssao_active();
+                        ui.checkbox(im_str!("ssao"), &mut ssao);
+                        post_effect.set_ssao(ssao);
+                        let ssao_options = &mut post_effect.ssao;
+                        ui.input_float(im_str!("radius"), &mut ssao_options.radius)
+                            .step(0.05)
+                            .step_fast(0.5)
+                            .build();
+                        ui.input_int(im_str!("samples"), &mut ssao_options.samples)
+                            .build();
+                        ui.input_float(im_str!("strength"), &mut ssao_options.strength)
+                            .step(0.1)
+                            .step_fast(1.0)
+                            .build();
+                        ui.input_float(im_str!("bias"), &mut ssao_options.bias)
+                            .step(0.005)
+                            .step_fast(0.05)
+                            .build();
+                        ui.input_float(im_str!("blur_sharpness"), &mut ssao_options.blur_sharpness)
+                            .step(0.5)
+                            .step_fast(4.0)
+                            .build();
+                        ui.pop_id();
+                    }
+                    // anti-aliasing editor
+                    if ui.collapsing_header(im_str!("Anti-aliasing")).build() {
+                        let mut fxaa = post_effect.pass_active("fxaa");
+                        ui.checkbox(im_str!("fxaa"), &mut fxaa);
+                        post_effect.set_fxaa(fxaa);
+                        let mut taa = post_effect.pass_active("taa");
+                        ui.checkbox(im_str!("taa"), &mut taa);
+                        post_effect.set_taa(taa);
+                        let anti_aliasing = &mut post_effect.anti_aliasing;
+                        ui.input_float(im_str!("fxaa_span_max"), &mut anti_aliasing.fxaa_span_max)
+                            .step(1.0)
+                            .step_fast(4.0)
+                            .build();
+                        ui.input_float(im_str!("taa_feedback"), &mut anti_aliasing.taa_feedback)
+                            .step(0.01)
+                            .step_fast(0.1)
+                            .build();
+                        ui.input_float(
+                            im_str!("taa_jitter_scale"),
+                            &mut anti_aliasing.taa_jitter_scale,
+                        )
+                        .step(0.1)
+                        .step_fast(0.5)
+                        .build();
+                    }
+                    // colour grading editor
+                    if ui.collapsing_header(im_str!("Colour grading")).build() {
+                        let grading = &mut post_effect.color_grading;
+                        ui.text(im_str!("lut: {:?}", grading.lut));
+                        ui.text(im_str!("target_lut: {:?}", grading.target_lut));
+                        ui.input_float(im_str!("blend_duration"), &mut grading.blend_duration)
+                            .step(0.1)
+                            .step_fast(1.0)
+                            .build();
+                        ui.input_float(im_str!("amount"), &mut grading.amount)
+                            .step(0.05)
+                            .step_fast(0.25)
+                            .build();
+                    }
+                    // motion blur editor
+                    if ui.collapsing_header(im_str!("Motion blur")).build() {
+                        ui.push_id(im_str!("motion_blur"));
+                        let mut motion_blur = post_effect.velocity_active();
+                        ui.checkbox(im_str!("motion_blur"), &mut motion_blur);
+                        post_effect.set_motion_blur(motion_blur);
+                        let motion_blur = &mut post_effect.motion_blur;
+                        ui.input_float(im_str!("shutter_angle"), &mut motion_blur.shutter_angle)
+                            .step(15.0)
+                            .step_fast(90.0)
+                            .build();
+                        ui.input_int(im_str!("samples"), &mut motion_blur.samples)
+                            .build();
+                        ui.input_float(im_str!("max_length"), &mut motion_blur.max_length)
+                            .step(0.01)
+                            .step_fast(0.05)
+                            .build();
+                        ui.pop_id();
+                    }
+                    // bloom editor
+                    if ui.collapsing_header(im_str!("Bloom")).build() {
+                        // keep labels shared with other sections distinct
+                        ui.push_id(im_str!("bloom"));
+                        let mut bloom = post_effect.bloom_active();
+                        ui.checkbox(im_str!("bloom"), &mut bloom);
+                        post_effect.set_bloom(bloom);
+                        let bloom_options = &mut post_effect.bloom;
+                        ui.input_float(im_str!("threshold"), &mut bloom_options.threshold)
+                            .step(0.05)
+                            .step_fast(0.5)
+                            .build();
+                        ui.input_float(im_str!("soft_knee"), &mut bloom_options.soft_knee)
+                            .step(0.05)
+                            .step_fast(0.25)
+                            .build();
+                        ui.input_float(im_str!("intensity"), &mut bloom_options.intensity)
+                            .step(0.01)
+                            .step_fast(0.1)
+                            .build();
+                        ui.input_float(im_str!("radius"), &mut bloom_options.radius)
+                            .step(0.1)
+                            .step_fast(0.5)
+                            .build();
+                        ui.pop_id();
+                    }
+                    // tone mapping editor
+                    if ui.collapsing_header(im_str!("Tone mapping")).build() {
+                        let mut hdr = post_effect.hdr();
+                        ui.checkbox(im_str!("hdr"), &mut hdr);
+                        let tone_mapping = &mut post_effect.tone_mapping;
+                        let mut operator = match tone_mapping.operator {
+                            ToneMapOperator::Reinhard => 0,
+                            ToneMapOperator::Aces => 1,
+                            ToneMapOperator::Filmic => 2,
+                        };
+                        ui.combo(
+                            im_str!("operator"),
+                            &mut operator,
+                            &[im_str!("Reinhard"), im_str!("ACES"), im_str!("Filmic")],
+                            -1,
+                        );
+                        tone_mapping.operator = match operator {
+                            0 => ToneMapOperator::Reinhard,
+                            2 => ToneMapOperator::Filmic,
+                            _ => ToneMapOperator::Aces,
+                        };
+                        ui.input_float(im_str!("exposure"), &mut tone_mapping.exposure)
+                            .step(0.1)
+                            .step_fast(1.0)
+                            .build();
+                        ui.checkbox(im_str!("auto_exposure"), &mut tone_mapping.auto_exposure);
+                        ui.input_float(im_str!("exposure_key"), &mut tone_mapping.exposure_key)
+                            .step(0.01)
+                            .step_fast(0.1)
+                            .build();
+                        ui.input_float(
+                            im_str!("adaptation_speed"),
+                            &mut tone_mapping.adaptation_speed,
+                        )
+                        .step(0.1)
+                        .step_fast(1.0)
+                        .build();
+                        post_effect.set_hdr(hdr);
+                    }
+                    // accessibility editor
+                    if ui.collapsing_header(im_str!("Accessibility")).build() {
+                        ui.push_id(im_str!("accessibility"));
+                        let mut color_blind = post_effect.color_blind.clone();
+                        let mut deficiency = match color_blind.deficiency {
+                            ColorBlindDeficiency::None => 0,
+                            ColorBlindDeficiency::Protanopia => 1,
+                            ColorBlindDeficiency::Deuteranopia => 2,
+                            ColorBlindDeficiency::Tritanopia => 3,
+                        };
+                        ui.combo(
+                            im_str!("color_blindness"),
+                            &mut deficiency,
+                            &[
+                                im_str!("None"),
+                                im_str!("Protanopia"),
+                                im_str!("Deuteranopia"),
+                                im_str!("Tritanopia"),
+                            ],
+                            -1,
+                        );
+                        color_blind.deficiency = match deficiency {
+                            1 => ColorBlindDeficiency::Protanopia,
+                            2 => ColorBlindDeficiency::Deuteranopia,
+                            3 => ColorBlindDeficiency::Tritanopia,
+                            _ => ColorBlindDeficiency::None,
+                        };
+                        let mut mode = match color_blind.mode {
+                            ColorBlindMode::Daltonize => 0,
+                            ColorBlindMode::Simulate => 1,
+                        };
+                        ui.combo(
+                            im_str!("mode"),
+                            &mut mode,
+                            &[im_str!("Daltonize"), im_str!("Simulate")],
+                            -1,
+                        );
+                        color_blind.mode = match mode {
+                            1 => ColorBlindMode::Simulate,
+                            _ => ColorBlindMode::Daltonize,
+                        };
+                        ui.input_float(im_str!("strength"), &mut color_blind.strength)
+                            .step(0.1)
+                            .step_fast(0.5)
+                            .build();
+                        post_effect.set_color_blind(color_blind);
+                        ui.pop_id();
+                    }
+                    // fog editor
+                    if ui.collapsing_header(im_str!("Fog")).build() {
+                        ui.push_id(im_str!("fog"));
+                        ui.checkbox(im_str!("active"), &mut fog.active);
+                        let mut mode = fog.mode_index();
+                        ui.combo(
+                            im_str!("mode"),
+                            &mut mode,
+                            &[
+                                im_str!("Linear"),
+                                im_str!("Exponential"),
+                                im_str!("ExponentialSquared"),
+                            ],
+                            -1,
+                        );
+                        fog.mode = FogMode::from_index(mode);
+                        if sky.is_some() {
+                            ui.text(im_str!("colour is set by the sky"));
+                        }
+                        ui.input_float(im_str!("r"), &mut fog.color.0)
+                            .step(0.01)
+                            .step_fast(0.1)
+                            .build();
+                        ui.input_float(im_str!("g"), &mut fog.color.1)
+                            .step(0.01)
+                            .step_fast(0.1)
+                            .build();
+                        ui.input_float(im_str!("b"), &mut fog.color.2)
+                            .step(0.01)
+                            .step_fast(0.1)
+                            .build();
+                        ui.input_float(im_str!("density"), &mut fog.density)
+                            .step(0.001)
+                            .step_fast(0.01)
+                            .build();
+                        ui.input_float(im_str!("start"), &mut fog.start)
+                            .step(1.0)
+                            .step_fast(10.0)
+                            .build();
+                        ui.input_float(im_str!("end"), &mut fog.end)
+                            .step(1.0)
+                            .step_fast(10.0)
+                            .build();
+                        ui.input_float(im_str!("height_falloff"), &mut fog.height_falloff)
+                            .step(0.01)
+                            .step_fast(0.1)
+                            .build();
+                        ui.pop_id();
+                    }
+                    // sky editor
+                    if let Some(ref mut sky) = *sky {
+                        if ui.collapsing_header(im_str!("Sky")).build() {
+                            ui.push_id(im_str!("sky"));
+                            ui.input_float(im_str!("time_of_day"), &mut sky.time_of_day)
+                                .step(0.1)
+                                .step_fast(1.0)
+                                .build();
+                            ui.checkbox(im_str!("real_time"), &mut sky.real_time);
+                            ui.input_float(im_str!("day_length"), &mut sky.day_length)
+                                .step(10.0)
+                                .step_fast(100.0)
+                                .build();
+                            ui.input_float(im_str!("turbidity"), &mut sky.turbidity)
+                                .step(0.1)
+                                .step_fast(1.0)
+                                .build();
+                            ui.input_float(im_str!("intensity"), &mut sky.intensity)
+                                .step(0.1)
+                                .step_fast(0.5)
+                                .build();
+                            ui.pop_id();
+                        }
+                    }
+                    // skybox editor
+                    if let Some(ref mut skybox) = *skybox {
+                        if ui.collapsing_header(im_str!("Skybox")).build() {
+                            ui.push_id(im_str!("skybox"));
+                            ui.input_float(im_str!("intensity"), &mut skybox.intensity)
+                                .step(0.1)
+                                .step_fast(0.5)
+                                .build();
+                            ui.pop_id();
+                        }
+                    }
                     // render items editor
                     if ui.collapsing_header(im_str!("Render items")).build() {
                         // create node for each item
-                        for render_item in render_items {
+                        for render_item in render_items.iter_mut() {
                             ui.tree_node(im_str!("name:{}", render_item.name))
                                 .build(|| {
                                     ui.checkbox(im_str!("active"), &mut render_item.active);
@@ -681,6 +1424,49 @@ impl Draw for Renderer {
                                         render_item.instance_transforms.len()
                                     ));
                                     ui.text(im_str!("vert_count:{}", render_item.vertices.len()));
+                                    // tessellation of the material
+                                    if ui.collapsing_header(im_str!("tessellation")).build() {
+                                        let tessellation = &mut render_item.material.tessellation;
+                                        ui.input_float(
+                                            im_str!("inner_level"),
+                                            &mut tessellation.inner_level,
+                                        )
+                                        .step(1.0)
+                                        .step_fast(4.0)
+                                        .build();
+                                        ui.input_float(
+                                            im_str!("outer_level"),
+                                            &mut tessellation.outer_level,
+                                        )
+                                        .step(1.0)
+                                        .step_fast(4.0)
+                                        .build();
+                                        ui.checkbox(
+                                            im_str!("adaptive"),
+                                            &mut tessellation.adaptive,
+                                        );
+                                        ui.input_float(
+                                            im_str!("near_distance"),
+                                            &mut tessellation.near_distance,
+                                        )
+                                        .step(1.0)
+                                        .step_fast(10.0)
+                                        .build();
+                                        ui.input_float(
+                                            im_str!("far_distance"),
+                                            &mut tessellation.far_distance,
+                                        )
+                                        .step(1.0)
+                                        .step_fast(10.0)
+                                        .build();
+                                        ui.input_float(
+                                            im_str!("displacement_scale"),
+                                            &mut tessellation.displacement_scale,
+                                        )
+                                        .step(0.1)
+                                        .step_fast(1.0)
+                                        .build();
+                                    }
                                 });
                         }
                     }
@@ -746,6 +1532,27 @@ impl Draw for Renderer {
                 });
         }
 
+        // show shaders loaded from files that failed to compile and broken materials
+        if !renderer.shaders.errors.is_empty() || !renderer.material_warnings.is_empty() {
+            let errors = &renderer.shaders.errors;
+            let material_warnings = &renderer.material_warnings;
+            ui.window(im_str!("shader errors"))
+                .size((500.0, 200.0), ImGuiCond::FirstUseEver)
+                .position((width as f32 - 500.0, 0.0), ImGuiCond::FirstUseEver)
+                .collapsible(true)
+                .build(|| {
+                    for (&(ref name, target), error) in errors {
+                        ui.text(im_str!("{:?} shader {}: {}", target, name, error));
+                    }
+                    for (&index, warning) in material_warnings {
+                        let name = render_items
+                            .get(index)
+                            .map_or("", |item| item.name.as_str());
+                        ui.text(im_str!("Render item {} {}: {}", index, name, warning));
+                    }
+                });
+        }
+
         // render imgui items
         let mut target = target.lock().unwrap();
         renderer.imgui_rend.render(&mut *target, ui).unwrap();
@@ -1,22 +1,38 @@
-use glium::backend::Facade;
+use glium::backend::{Context, Facade};
 use glium::framebuffer::SimpleFrameBuffer;
 use glium::index::{IndexBuffer, PrimitiveType};
-use glium::texture::{DepthFormat, DepthTexture2d, MipmapsOption, Texture2d};
+use glium::texture::{
+    DepthFormat, DepthTexture2d, MipmapsOption, Texture2d, UncompressedFloatFormat,
+};
+use glium::uniforms::{AsUniformValue, UniformValue, Uniforms};
 use glium::vertex::VertexBuffer;
-use glium::Surface;
+use glium::{DrawParameters, Rect, Surface};
 
-use types::Vertex;
+use lut::{CubeData, Lut};
+use shader::Shaders;
+use transition::{Transition, TransitionOptions, TransitionType};
+use types::{Matrix4, Vertex};
 
+use std::collections::HashMap;
+use std::rc::Rc;
 use time;
 
+/// The number of jitter positions cycled through for temporal anti-aliasing
+const TAA_SAMPLES: u64 = 8;
+
+/// The number of times the bloom is halved in size before being blurred back up
+const BLOOM_LEVELS: usize = 4;
+
 /// struct representing a post effect
 pub struct PostEffect {
+    /// Ref to the render context
+    context: Rc<Context>,
     /// The vertex buffer to render
     pub vertex_buffer: VertexBuffer<Vertex>,
     /// The index buffer to render
     pub index_buffer: IndexBuffer<u16>,
-    /// The current shader being used for post processing
-    pub current_shader: &'static str,
+    /// The passes run in order on each rendered camera, the last active pass draws to screen
+    pub passes: Vec<PostPass>,
     /// The time the post effect was initialised
     pub start_time: f32,
     /// The scale factor that the scene will be rendered
@@ -24,6 +40,30 @@ pub struct PostEffect {
     pub downscale_factor: f32,
     /// Options for the default post shader
     pub post_shader_options: PostShaderOptions,
+    /// Whether the scene and post passes are rendered to floating point targets
+    hdr: bool,
+    /// Options for the tone mapping and eye adaptation passes
+    pub tone_mapping: ToneMappingOptions,
+    /// Options for the bloom passes
+    pub bloom: BloomOptions,
+    /// Options for the ambient occlusion passes
+    pub ssao: SsaoOptions,
+    /// Options for the anti-aliasing passes
+    pub anti_aliasing: AntiAliasingOptions,
+    /// Options for the colour grading pass
+    pub color_grading: ColorGradingOptions,
+    /// Options for the motion blur pass
+    pub motion_blur: MotionBlurOptions,
+    /// Options for the colour blindness pass, change with set_color_blind
+    pub color_blind: ColorBlindOptions,
+    /// The colour lookup tables available for grading
+    luts: HashMap<String, Lut>,
+    /// The colours an interrupted blend had reached, blended from instead of color_grading.lut
+    blend_start: Option<Lut>,
+    /// The screen transition that is running
+    transition: Option<Transition>,
+    /// The names of the built in passes making up each effect, turned on and off together
+    effect_passes: HashMap<&'static str, Vec<String>>,
 }
 
 impl PostEffect {
@@ -57,96 +97,1208 @@ impl PostEffect {
 
         let ind_arr = [1 as u16, 2, 0, 3];
 
+        let mut ssao = PostPassBuilder::default()
+            .name("ssao_blur")
+            .shader_name("ssao_blur")
+            .active(false)
+            .build()
+            .unwrap();
+        ssao.set_input("ao_tex", "ssao");
+
+        // occlusion and bloom run first so they work on the untouched HDR scene
+        let mut passes = vec![
+            PostPassBuilder::default()
+                .name("ssao")
+                .shader_name("ssao")
+                .active(false)
+                .scale(0.5f32)
+                .chained(false)
+                .build()
+                .unwrap(),
+            ssao,
+        ];
+        passes.push(
+            PostPassBuilder::default()
+                .name("taa")
+                .shader_name("taa")
+                .active(false)
+                .history(true)
+                .build()
+                .unwrap(),
+        );
+        passes.push(
+            PostPassBuilder::default()
+                .name("motion_blur")
+                .shader_name("motion_blur")
+                .active(false)
+                .build()
+                .unwrap(),
+        );
+        let mut bloom = bloom_passes(BLOOM_LEVELS);
+        let bloom_names = bloom.iter().map(|p| p.name.clone()).collect();
+        passes.append(&mut bloom);
+        passes.append(&mut vec![
+            PostPassBuilder::default()
+                .name("adapt")
+                .shader_name("adapt")
+                .active(false)
+                .size(Some((1, 1)))
+                .history(true)
+                .chained(false)
+                .build()
+                .unwrap(),
+            PostPassBuilder::default()
+                .name("tonemap")
+                .shader_name("tonemap")
+                .active(false)
+                .build()
+                .unwrap(),
+            PostPassBuilder::default()
+                .name("grading")
+                .shader_name("grading")
+                .active(false)
+                .build()
+                .unwrap(),
+            PostPassBuilder::default()
+                .name("color_blind")
+                .shader_name("color_blind")
+                .active(false)
+                .build()
+                .unwrap(),
+            PostPassBuilder::default()
+                .name("fxaa")
+                .shader_name("fxaa")
+                .active(false)
+                .build()
+                .unwrap(),
+            PostPassBuilder::default()
+                .name("transition")
+                .shader_name("transition")
+                .active(false)
+                .build()
+                .unwrap(),
+            PostPass::default(),
+        ]);
+
+        let mut effect_passes = HashMap::new();
+        effect_passes.insert("ssao", vec!["ssao".to_string(), "ssao_blur".to_string()]);
+        effect_passes.insert("bloom", bloom_names);
+        for name in &[
+            "taa",
+            "motion_blur",
+            "grading",
+            "color_blind",
+            "fxaa",
+            "transition",
+        ] {
+            effect_passes.insert(*name, vec![name.to_string()]);
+        }
+
         PostEffect {
+            context: facade.get_context().clone(),
             vertex_buffer: VertexBuffer::new(facade, &vert_arr).unwrap(),
             index_buffer: IndexBuffer::new(facade, PrimitiveType::TriangleStrip, &ind_arr).unwrap(),
-            current_shader: "default",
+            passes,
             start_time: time::precise_time_s() as f32,
             downscale_factor: 1.0f32,
             post_shader_options: PostShaderOptions::default(),
+            hdr: false,
+            tone_mapping: ToneMappingOptions::default(),
+            bloom: BloomOptions::default(),
+            ssao: SsaoOptions::default(),
+            anti_aliasing: AntiAliasingOptions::default(),
+            color_grading: ColorGradingOptions::default(),
+            motion_blur: MotionBlurOptions::default(),
+            color_blind: ColorBlindOptions::default(),
+            luts: HashMap::new(),
+            blend_start: None,
+            transition: None,
+            effect_passes,
         }
     }
 }
 
-/// Contains all the options for the default post shader
+impl PostEffect {
+    /// Add a pass to the end of the post processing chain, failing if a pass
+    /// has the same name or its post shader has not been added to shaders
+    pub fn add_pass(&mut self, shaders: &Shaders, pass: PostPass) -> Result<(), String> {
+        self.validate_pass(shaders, &pass)?;
+        self.passes.push(pass);
+        Ok(())
+    }
+
+    /// Insert a pass into the post processing chain at index, failing if a pass
+    /// has the same name or its post shader has not been added to shaders
+    pub fn insert_pass(
+        &mut self,
+        shaders: &Shaders,
+        index: usize,
+        pass: PostPass,
+    ) -> Result<(), String> {
+        self.validate_pass(shaders, &pass)?;
+        self.passes.insert(index, pass);
+        Ok(())
+    }
+
+    /// Remove the first pass with name from the chain, returning it if found
+    pub fn remove_pass(&mut self, name: &str) -> Option<PostPass> {
+        let index = self.passes.iter().position(|p| p.name == name)?;
+        Some(self.passes.remove(index))
+    }
+
+    /// Get a ref to a pass from its name, returning the first found
+    pub fn get_pass_by_name(&mut self, name: &str) -> Option<&mut PostPass> {
+        self.passes.iter_mut().find(|p| p.name == name)
+    }
+
+    /// Whether the pass with name is in the chain and active
+    pub fn pass_active(&self, name: &str) -> bool {
+        self.passes.iter().any(|p| p.name == name && p.active)
+    }
+
+    /// Whether the scene and post passes are rendered to floating point targets
+    pub fn hdr(&self) -> bool {
+        self.hdr
+    }
+
+    /// Turn HDR rendering on or off, activating the tone mapping pass and
+    /// the eye adaptation pass if auto_exposure is set in tone_mapping
+    pub fn set_hdr(&mut self, hdr: bool) {
+        self.hdr = hdr;
+        let auto_exposure = self.tone_mapping.auto_exposure;
+        if let Some(pass) = self.get_pass_by_name("tonemap") {
+            pass.active = hdr;
+        }
+        if let Some(pass) = self.get_pass_by_name("adapt") {
+            pass.active = hdr && auto_exposure;
+        }
+    }
+
+    /// Turn the bloom passes on or off
+    pub fn set_bloom(&mut self, bloom: bool) {
+        self.set_effect_active("bloom", bloom);
+    }
+
+    /// Whether the bloom passes are on
+    pub fn bloom_active(&self) -> bool {
+        self.pass_active("bloom")
+    }
+
+    /// Turn the ambient occlusion passes on or off
+    pub fn set_ssao(&mut self, ssao: bool) {
+        self.set_effect_active("ssao", ssao);
+    }
+
+    /// Whether the ambient occlusion passes are on
+    pub fn ssao_active(&self) -> bool {
+        self.pass_active("ssao")
+    }
+
+    /// Turn the FXAA pass on or off
+    pub fn set_fxaa(&mut self, fxaa: bool) {
+        self.set_effect_active("fxaa", fxaa);
+    }
+
+    /// Turn the temporal anti-aliasing pass and camera jitter on or off
+    pub fn set_taa(&mut self, taa: bool) {
+        self.set_effect_active("taa", taa);
+    }
+
+    /// The sub-pixel offset in normalised device coordinates to jitter the projection by on
+    /// frame, zero when temporal anti-aliasing is off
+    pub fn taa_jitter(&self, frame: u64, dimensions: (u32, u32)) -> (f32, f32) {
+        if !self.pass_active("taa") {
+            return (0f32, 0f32);
+        }
+
+        let index = frame % TAA_SAMPLES + 1;
+        let scale = self.anti_aliasing.taa_jitter_scale;
+        (
+            (halton(index, 2) - 0.5f32) * 2f32 * scale / dimensions.0 as f32,
+            (halton(index, 3) - 0.5f32) * 2f32 * scale / dimensions.1 as f32,
+        )
+    }
+
+    /// Turn the motion blur pass and the velocity buffer it reads on or off,
+    /// motion blur needs floating point targets to hold the signed velocity
+    pub fn set_motion_blur(&mut self, motion_blur: bool) {
+        let motion_blur = motion_blur && self.float_targets();
+        self.set_effect_active("motion_blur", motion_blur);
+    }
+
+    /// Whether a pass needs the per-pixel velocity of the scene and it can be rendered
+    pub fn velocity_active(&self) -> bool {
+        self.pass_active("motion_blur") && self.float_targets()
+    }
+
+    /// Whether floating point targets can be rendered to, otherwise targets are 8-bit
+    pub fn float_targets(&self) -> bool {
+        // GLES can only render to float textures with an extension
+        UncompressedFloatFormat::F16F16F16F16.is_color_renderable(&*self.context)
+    }
+
+    /// Set the colour blindness options, the pass runs unless the deficiency is None
+    pub fn set_color_blind(&mut self, color_blind: ColorBlindOptions) {
+        let active = color_blind.deficiency != ColorBlindDeficiency::None;
+        self.color_blind = color_blind;
+        self.set_effect_active("color_blind", active);
+    }
+
+    /// Add a colour lookup table that can be graded with
+    pub fn add_lut(&mut self, name: &str, lut: Lut) {
+        self.luts.insert(name.to_string(), lut);
+    }
+
+    /// Remove a colour lookup table, returning it if found
+    pub fn remove_lut(&mut self, name: &str) -> Option<Lut> {
+        self.luts.remove(name)
+    }
+
+    /// The names of the colour lookup tables that have been added
+    pub fn lut_names(&self) -> Vec<&str> {
+        self.luts.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// Grade with the lookup table name straight away, None turns grading off
+    pub fn set_lut(&mut self, name: Option<&str>) {
+        self.color_grading.lut = name.map(|n| n.to_string());
+        self.color_grading.target_lut = None;
+        self.color_grading.blend = 0f32;
+        self.blend_start = None;
+        self.set_effect_active("grading", name.is_some());
+    }
+
+    /// Blend from the current lookup table to name over duration seconds,
+    /// a blend that is already running carries on from the colours it has reached
+    pub fn blend_to_lut(&mut self, name: &str, duration: f32) -> Result<(), String> {
+        if !self.luts.contains_key(name) {
+            return Err(format!("No LUT named {}", name));
+        }
+
+        // bake the colours of the running blend into the table blended from
+        let mixed = {
+            let grading = &self.color_grading;
+            let from = self
+                .blend_start
+                .as_ref()
+                .or_else(|| grading.lut.as_ref().and_then(|n| self.luts.get(n)));
+            let to = grading.target_lut.as_ref().and_then(|n| self.luts.get(n));
+            match (from, to) {
+                (Some(from), Some(to)) => Some(CubeData::mix(&from.data, &to.data, grading.blend)),
+                _ => None,
+            }
+        };
+        self.blend_start = match mixed {
+            Some(mixed) => Some(Lut::from_data(&self.context, mixed)?),
+            None => None,
+        };
+
+        if self.color_grading.lut.is_none() {
+            self.color_grading.lut = Some(name.to_string());
+        }
+        self.color_grading.target_lut = Some(name.to_string());
+        self.color_grading.blend = 0f32;
+        self.color_grading.blend_duration = duration;
+        self.set_effect_active("grading", true);
+        Ok(())
+    }
+
+    /// Start a screen transition, replacing any running one
+    ///
+    /// Use Game::start_transition to be called back when the screen is covered
+    pub fn start_transition(&mut self, options: TransitionOptions) {
+        self.transition = Some(Transition::new(options));
+        self.set_effect_active("transition", true);
+    }
+
+    /// Stop the running screen transition
+    pub fn cancel_transition(&mut self) {
+        self.transition = None;
+        self.set_effect_active("transition", false);
+    }
+
+    /// Whether a screen transition is running
+    pub fn transition_active(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    /// Advance the running screen transition by delta seconds
+    ///
+    /// Returns true on the update the screen becomes fully covered
+    pub fn update_transition(&mut self, delta: f32) -> bool {
+        let (covered, finished, capturing) = match self.transition {
+            Some(ref mut transition) => {
+                let covered = transition.update(delta);
+                (covered, transition.finished(), transition.capturing())
+            }
+            None => return false,
+        };
+
+        if finished {
+            self.cancel_transition();
+        } else if let Some(pass) = self.get_pass_by_name("transition") {
+            // keep the frame for a crossfade to blend from
+            pass.history = capturing;
+        }
+
+        covered
+    }
+
+    /// Advance any time based post effects by delta seconds
+    pub fn update(&mut self, delta: f32) {
+        let grading = &mut self.color_grading;
+        if grading.target_lut.is_some() {
+            grading.blend += delta / grading.blend_duration.max(0.0001f32);
+            if grading.blend >= 1f32 {
+                grading.lut = grading.target_lut.take();
+                grading.blend = 0f32;
+                self.blend_start = None;
+            }
+        }
+    }
+
+    /// Sets active on the built in passes of effect
+    fn set_effect_active(&mut self, effect: &str, active: bool) {
+        let names = &self.effect_passes[effect];
+        for pass in self.passes.iter_mut().filter(|p| names.contains(&p.name)) {
+            pass.active = active;
+        }
+    }
+
+    /// Checks a pass has a unique name and its post shader has been added
+    fn validate_pass(&self, shaders: &Shaders, pass: &PostPass) -> Result<(), String> {
+        if self.passes.iter().any(|p| p.name == pass.name) {
+            return Err(format!(
+                "A post pass named {} is already in the chain",
+                pass.name
+            ));
+        }
+        if !shaders.post_shaders.contains_key(&pass.shader_name) {
+            return Err(format!(
+                "Post pass {} uses missing post shader {}",
+                pass.name, pass.shader_name
+            ));
+        }
+        Ok(())
+    }
+
+    /// Creates a colour texture for the scene or a pass, floating point if float is set
+    fn create_target(&self, dimensions: (u32, u32), float: bool) -> Texture2d {
+        if float && self.float_targets() {
+            Texture2d::empty_with_format(
+                &self.context,
+                UncompressedFloatFormat::F16F16F16F16,
+                MipmapsOption::NoMipmap,
+                dimensions.0,
+                dimensions.1,
+            )
+            .unwrap()
+        } else {
+            Texture2d::empty(&self.context, dimensions.0, dimensions.1).unwrap()
+        }
+    }
+}
+
+/// A value that can be passed to a post pass shader as a uniform
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PostUniform {
+    /// float uniform
+    Float(f32),
+    /// vec2 uniform
+    Vec2((f32, f32)),
+    /// vec3 uniform
+    Vec3((f32, f32, f32)),
+    /// vec4 uniform
+    Vec4((f32, f32, f32, f32)),
+    /// int uniform
+    Int(i32),
+    /// bool uniform
+    Bool(bool),
+}
+
+impl AsUniformValue for PostUniform {
+    fn as_uniform_value(&self) -> UniformValue {
+        match *self {
+            PostUniform::Float(v) => UniformValue::Float(v),
+            PostUniform::Vec2(v) => UniformValue::Vec2([v.0, v.1]),
+            PostUniform::Vec3(v) => UniformValue::Vec3([v.0, v.1, v.2]),
+            PostUniform::Vec4(v) => UniformValue::Vec4([v.0, v.1, v.2, v.3]),
+            PostUniform::Int(v) => UniformValue::SignedInt(v),
+            PostUniform::Bool(v) => UniformValue::Bool(v),
+        }
+    }
+}
+
+/// A single pass in the post processing chain
 #[derive(Builder, Clone, PartialEq)]
 #[builder(default)]
-pub struct PostShaderOptions {
-    /// The offset for the chromatic aberration
-    pub chrom_offset: f32,
-    /// The mix amount for the chromatic aberration, if this is at 0.0 chromatic aberration is off
-    pub chrom_amt: f32,
-    /// Whether blur is on
-    pub blur: bool,
-    /// The amount of blur
-    pub blur_amt: f32,
-    /// The radius of the blur
-    pub blur_radius: f32,
-    /// The weight of the blur
-    pub blur_weight: f32,
-    /// Whether bokeh is on
-    pub bokeh: bool,
-    /// Bokeh focal depth
-    pub bokeh_focal_depth: f32,
-    /// Bokeh focal width
-    pub bokeh_focal_width: f32,
-    /// Colour grading
-    pub color_offset: (f32, f32, f32, f32),
-    /// Greyscale
-    pub greyscale: bool,
-    /// Noise
-    pub noise: f32,
-}
-
-impl Default for PostShaderOptions {
+pub struct PostPass {
+    /// The name of the pass, its output is available to later passes as `<name>_tex`
+    #[builder(setter(into))]
+    pub name: String,
+    /// The name of the post shader used to render the pass
+    #[builder(setter(into))]
+    pub shader_name: String,
+    /// Whether the pass is run
+    pub active: bool,
+    /// The size of the pass render target in relation to the camera viewport
+    pub scale: f32,
+    /// A fixed pixel size for the pass render target, overriding scale
+    pub size: Option<(u32, u32)>,
+    /// Whether the output is kept for the next frame, available to the pass as `<name>_history`
+    pub history: bool,
+    /// Whether later passes read the output as `tex`, off for passes that only produce data
+    pub chained: bool,
+    /// Samplers bound to the outputs of earlier passes, from uniform name to pass name,
+    /// an input named `tex` replaces the chained output
+    pub inputs: HashMap<String, String>,
+    /// Extra uniforms passed to the pass shader
+    pub uniforms: HashMap<String, PostUniform>,
+}
+
+impl Default for PostPass {
     fn default() -> Self {
-        PostShaderOptions {
-            chrom_offset: 0.003f32,
-            chrom_amt: 0.0f32,
-            blur: false,
-            blur_amt: 1.0f32,
-            blur_radius: 1.0f32,
-            blur_weight: 1.0f32,
-            bokeh: false,
-            bokeh_focal_depth: 0.2f32,
-            bokeh_focal_width: 0.2f32,
-            color_offset: (1f32, 1f32, 1f32, 1f32),
-            greyscale: false,
-            noise: 0f32,
+        PostPass {
+            name: "default".into(),
+            shader_name: "default".into(),
+            active: true,
+            scale: 1f32,
+            size: None,
+            history: false,
+            chained: true,
+            inputs: HashMap::new(),
+            uniforms: HashMap::new(),
         }
     }
 }
 
-/// Renders the post effect on to the scene rendered in the draw FnMut
-pub fn render_to_texture<T, F, C>(
-    system: &PostEffect,
+impl PostPass {
+    /// Set a uniform that will be passed to the pass shader
+    pub fn set_uniform(&mut self, name: &str, value: PostUniform) {
+        self.uniforms.insert(name.to_string(), value);
+    }
+
+    /// Bind the output of the earlier pass pass_name to the sampler uniform name
+    pub fn set_input(&mut self, name: &str, pass_name: &str) {
+        self.inputs.insert(name.to_string(), pass_name.to_string());
+    }
+}
+
+/// Declares an options struct with a builder and defaults, each field with a uniform name
+/// is passed to the post shaders under it
+macro_rules! post_options {
+    (
+        $(#[$struct_attr:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$field_attr:meta])*
+                pub $field:ident: $ty:ty = $default:expr $(=> $uniform:expr)*,
+            )*
+        }
+    ) => {
+        $(#[$struct_attr])*
+        #[derive(Builder, Clone, PartialEq)]
+        #[builder(default)]
+        pub struct $name {
+            $(
+                $(#[$field_attr])*
+                pub $field: $ty,
+            )*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl Uniforms for $name {
+            fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut f: F) {
+                $($(f($uniform, self.$field.as_uniform_value());)*)*
+            }
+        }
+    };
+}
+
+post_options! {
+    /// Contains all the options for the default post shader
+    pub struct PostShaderOptions {
+        /// The offset for the chromatic aberration
+        pub chrom_offset: f32 = 0.003f32 => "chrom_offset",
+        /// The mix amount for the chromatic aberration,
+        /// if this is at 0.0 chromatic aberration is off
+        pub chrom_amt: f32 = 0.0f32 => "chrom_amt",
+        /// Whether blur is on
+        pub blur: bool = false => "blur",
+        /// The amount of blur
+        pub blur_amt: f32 = 1.0f32 => "blur_amt",
+        /// The radius of the blur
+        pub blur_radius: f32 = 1.0f32 => "blur_radius",
+        /// The weight of the blur
+        pub blur_weight: f32 = 1.0f32 => "blur_weight",
+        /// Whether bokeh is on
+        pub bokeh: bool = false => "bokeh",
+        /// Bokeh focal depth
+        pub bokeh_focal_depth: f32 = 0.2f32 => "bokeh_focal_depth",
+        /// Bokeh focal width
+        pub bokeh_focal_width: f32 = 0.2f32 => "bokeh_focal_width",
+        /// Colour grading
+        pub color_offset: (f32, f32, f32, f32) = (1f32, 1f32, 1f32, 1f32) => "color_offset",
+        /// Greyscale
+        pub greyscale: bool = false => "greyscale",
+        /// Noise
+        pub noise: f32 = 0f32 => "noise",
+    }
+}
+
+/// The curve used to map HDR colours in to displayable range
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ToneMapOperator {
+    /// Simple Reinhard operator
+    Reinhard,
+    /// ACES filmic curve approximation
+    Aces,
+    /// Uncharted 2 style filmic curve
+    Filmic,
+}
+
+impl AsUniformValue for ToneMapOperator {
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::SignedInt(match *self {
+            ToneMapOperator::Reinhard => 0,
+            ToneMapOperator::Aces => 1,
+            ToneMapOperator::Filmic => 2,
+        })
+    }
+}
+
+post_options! {
+    /// Contains all the options for tone mapping and eye adaptation
+    pub struct ToneMappingOptions {
+        /// The tone mapping curve
+        pub operator: ToneMapOperator = ToneMapOperator::Aces => "tonemap_operator",
+        /// Exposure multiplier applied before tone mapping
+        pub exposure: f32 = 1f32 => "exposure",
+        /// Whether exposure adapts to the average scene luminance
+        pub auto_exposure: bool = false,
+        /// The luminance the average scene luminance is adapted to
+        pub exposure_key: f32 = 0.18f32 => "exposure_key",
+        /// How quickly the eye adapts to changes in luminance
+        pub adaptation_speed: f32 = 1.5f32 => "adaptation_speed",
+        /// The lowest average luminance adapted to
+        pub min_luminance: f32 = 0.03f32 => "min_luminance",
+        /// The highest average luminance adapted to
+        pub max_luminance: f32 = 8f32 => "max_luminance",
+    }
+}
+
+post_options! {
+    /// Contains all the options for the bloom passes
+    pub struct BloomOptions {
+        /// The brightness above which pixels bloom
+        pub threshold: f32 = 1f32 => "bloom_threshold",
+        /// How softly pixels below the threshold fade into the bloom, from 0 to 1
+        pub soft_knee: f32 = 0.5f32 => "bloom_soft_knee",
+        /// How strongly the bloom is added to the scene
+        pub intensity: f32 = 0.1f32 => "bloom_intensity",
+        /// The spread of the upsample filter, larger values give a wider glow
+        pub radius: f32 = 1f32 => "bloom_radius",
+    }
+}
+
+post_options! {
+    /// Contains all the options for the screen space ambient occlusion passes
+    pub struct SsaoOptions {
+        /// The view space radius around each pixel that is checked for occluders
+        pub radius: f32 = 0.5f32 => "ssao_radius",
+        /// The number of samples taken per pixel, at most 64
+        pub samples: i32 = 16 => "ssao_samples",
+        /// How strongly occlusion darkens the scene
+        pub strength: f32 = 1f32 => "ssao_strength",
+        /// Depth offset to stop surfaces occluding themselves
+        pub bias: f32 = 0.025f32 => "ssao_bias",
+        /// How strongly the blur avoids mixing across depth edges
+        pub blur_sharpness: f32 = 8f32 => "ssao_blur_sharpness",
+    }
+}
+
+post_options! {
+    /// Contains all the options for the FXAA and temporal anti-aliasing passes
+    pub struct AntiAliasingOptions {
+        /// The furthest in pixels FXAA searches along an edge
+        pub fxaa_span_max: f32 = 8f32 => "fxaa_span_max",
+        /// How much the edge direction is reduced by local brightness
+        pub fxaa_reduce_mul: f32 = 1f32 / 8f32 => "fxaa_reduce_mul",
+        /// The smallest reduction of the edge direction
+        pub fxaa_reduce_min: f32 = 1f32 / 128f32 => "fxaa_reduce_min",
+        /// How much of the history is kept each frame, higher is smoother but smears more
+        pub taa_feedback: f32 = 0.9f32 => "taa_feedback",
+        /// The size of the camera jitter in pixels
+        pub taa_jitter_scale: f32 = 1f32,
+    }
+}
+
+post_options! {
+    /// Contains all the options for the motion blur pass
+    pub struct MotionBlurOptions {
+        /// The shutter angle in degrees, 360 blurs over the whole frame and 180 over half of it
+        pub shutter_angle: f32 = 180f32 => "motion_blur_shutter_angle",
+        /// The number of samples taken along the velocity, at most 32
+        pub samples: i32 = 8 => "motion_blur_samples",
+        /// The longest blur in screen space, where 1 is the whole screen
+        pub max_length: f32 = 0.05f32 => "motion_blur_max_length",
+    }
+}
+
+/// Denotes the type of colour blindness
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ColorBlindDeficiency {
+    /// No colour blindness
+    None,
+    /// Missing red cones
+    Protanopia,
+    /// Missing green cones
+    Deuteranopia,
+    /// Missing blue cones
+    Tritanopia,
+}
+
+impl AsUniformValue for ColorBlindDeficiency {
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::SignedInt(match *self {
+            ColorBlindDeficiency::None => 0,
+            ColorBlindDeficiency::Protanopia => 1,
+            ColorBlindDeficiency::Deuteranopia => 2,
+            ColorBlindDeficiency::Tritanopia => 3,
+        })
+    }
+}
+
+/// Denotes what the colour blindness pass does
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ColorBlindMode {
+    /// Shift colours so they can be told apart with the deficiency
+    Daltonize,
+    /// Show the scene as it is seen with the deficiency, for previewing
+    Simulate,
+}
+
+/// Passed to the shaders as whether to simulate the deficiency
+impl AsUniformValue for ColorBlindMode {
+    fn as_uniform_value(&self) -> UniformValue {
+        UniformValue::Bool(*self == ColorBlindMode::Simulate)
+    }
+}
+
+post_options! {
+    /// Contains all the options for the colour blindness pass
+    pub struct ColorBlindOptions {
+        /// The type of colour blindness corrected for or simulated
+        pub deficiency: ColorBlindDeficiency = ColorBlindDeficiency::None
+            => "color_blind_deficiency",
+        /// Whether to correct for or simulate the deficiency
+        pub mode: ColorBlindMode = ColorBlindMode::Daltonize => "color_blind_simulate",
+        /// How much of the filter is applied, from 0 to 1
+        pub strength: f32 = 1f32 => "color_blind_strength",
+    }
+}
+
+/// Contains all the options for the colour grading pass
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct ColorGradingOptions {
+    /// The name of the lookup table graded with
+    #[builder(setter(into))]
+    pub lut: Option<String>,
+    /// The name of the lookup table being blended to
+    #[builder(setter(into))]
+    pub target_lut: Option<String>,
+    /// How far the blend to target_lut has got, from 0 to 1
+    pub blend: f32,
+    /// How long the blend to target_lut takes in seconds
+    pub blend_duration: f32,
+    /// How much of the graded colour is used, from 0 to 1
+    pub amount: f32,
+}
+
+impl Default for ColorGradingOptions {
+    fn default() -> Self {
+        ColorGradingOptions {
+            lut: None,
+            target_lut: None,
+            blend: 0f32,
+            blend_duration: 1f32,
+            amount: 1f32,
+        }
+    }
+}
+
+/// Returns the index element of the halton sequence with base
+fn halton(mut index: u64, base: u64) -> f32 {
+    let mut fraction = 1f32;
+    let mut result = 0f32;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
+
+/// Creates the bloom passes: a bright pass, levels of downsampling, upsampling back to the
+/// bright pass size and the pass adding the result to the scene
+fn bloom_passes(levels: usize) -> Vec<PostPass> {
+    let level_pass = |name: String, shader_name: &str, level: usize| {
+        PostPassBuilder::default()
+            .name(name)
+            .shader_name(shader_name)
+            .active(false)
+            .scale(0.5f32.powi(level as i32 + 1))
+            .chained(false)
+            .build()
+            .unwrap()
+    };
+
+    let mut passes = vec![level_pass("bloom_bright".into(), "bloom_bright", 0)];
+
+    // each level halves the size of the one before
+    for level in 1..=levels {
+        let mut pass = level_pass(format!("bloom_down_{}", level), "bloom_downsample", level);
+        pass.set_input("tex", &bloom_level_name("bloom_down", level - 1));
+        passes.push(pass);
+    }
+
+    // blur back up, adding in the downsampled level of the same size
+    for level in (0..levels).rev() {
+        let mut pass = level_pass(format!("bloom_up_{}", level), "bloom_upsample", level);
+        let lower = if level + 1 == levels {
+            bloom_level_name("bloom_down", level + 1)
+        } else {
+            bloom_level_name("bloom_up", level + 1)
+        };
+        pass.set_input("tex", &lower);
+        pass.set_input("base_tex", &bloom_level_name("bloom_down", level));
+        passes.push(pass);
+    }
+
+    let mut composite = PostPassBuilder::default()
+        .name("bloom")
+        .shader_name("bloom")
+        .active(false)
+        .build()
+        .unwrap();
+    composite.set_input("bloom_tex", "bloom_up_0");
+    passes.push(composite);
+
+    passes
+}
+
+/// The name of a bloom pass at level, level 0 of the downsampling being the bright pass
+fn bloom_level_name(prefix: &str, level: usize) -> String {
+    if level == 0 && prefix == "bloom_down" {
+        "bloom_bright".into()
+    } else {
+        format!("{}_{}", prefix, level)
+    }
+}
+
+/// Pass outputs and camera matrices kept from the previous frame of a camera
+#[derive(Default)]
+pub struct PostHistory {
+    /// The number of frames drawn for the camera
+    pub frame: u64,
+    /// The projection matrix the camera used last frame, without jitter
+    pub projection_matrix: Option<Matrix4>,
+    /// The modelview matrix the camera used last frame
+    pub modelview_matrix: Option<Matrix4>,
+    /// The kept outputs keyed by `<name>_history`
+    textures: HashMap<String, Texture2d>,
+    /// Render targets reused the next frame, with whether they are floating point
+    targets: HashMap<String, (bool, Texture2d)>,
+    /// Depth targets reused the next frame
+    depth_targets: HashMap<String, DepthTexture2d>,
+}
+
+impl PostHistory {
+    /// Takes the colour target kept as name, creating a new one if there is none with the
+    /// dimensions and format
+    pub fn take_target(
+        &mut self,
+        system: &PostEffect,
+        name: &str,
+        dimensions: (u32, u32),
+        float: bool,
+    ) -> Texture2d {
+        match self.targets.remove(name) {
+            Some((kept_float, texture))
+                if kept_float == float && texture.dimensions() == dimensions =>
+            {
+                texture
+            }
+            _ => system.create_target(dimensions, float),
+        }
+    }
+
+    /// Takes the depth target kept as name, creating a new one if there is none with the
+    /// dimensions
+    pub fn take_depth_target(
+        &mut self,
+        system: &PostEffect,
+        name: &str,
+        dimensions: (u32, u32),
+    ) -> DepthTexture2d {
+        match self.depth_targets.remove(name) {
+            Some(texture) if texture.dimensions() == dimensions => texture,
+            _ => DepthTexture2d::empty_with_format(
+                &system.context,
+                DepthFormat::F32,
+                MipmapsOption::NoMipmap,
+                dimensions.0,
+                dimensions.1,
+            )
+            .unwrap(),
+        }
+    }
+
+    /// Keeps a colour target as name to be taken again next frame
+    pub fn keep_target(&mut self, name: &str, float: bool, texture: Texture2d) {
+        self.targets.insert(name.to_string(), (float, texture));
+    }
+
+    /// Keeps a depth target as name to be taken again next frame
+    pub fn keep_depth_target(&mut self, name: &str, texture: DepthTexture2d) {
+        self.depth_targets.insert(name.to_string(), texture);
+    }
+
+    /// Takes the colour and depth targets for rendering a camera's scene at the
+    /// viewport dimensions scaled by the downscale factor
+    pub fn take_scene_targets(
+        &mut self,
+        system: &PostEffect,
+        dimensions: (u32, u32),
+    ) -> (Texture2d, DepthTexture2d) {
+        let dimensions = (
+            (dimensions.0 as f32 * system.downscale_factor) as u32,
+            (dimensions.1 as f32 * system.downscale_factor) as u32,
+        );
+        (
+            self.take_target(system, "scene", dimensions, system.hdr),
+            self.take_depth_target(system, "scene", dimensions),
+        )
+    }
+
+    /// Keeps the textures a camera's scene was rendered to for the next frame
+    pub fn keep_scene(&mut self, system: &PostEffect, scene: SceneTextures) {
+        self.keep_target("scene", system.hdr, scene.color);
+        self.keep_depth_target("scene", scene.depth);
+        if let Some(velocity) = scene.velocity {
+            self.keep_target("velocity", true, velocity);
+        }
+    }
+}
+
+/// The textures a camera's scene was rendered to, read by the post passes
+pub struct SceneTextures {
+    /// The scene colour
+    pub color: Texture2d,
+    /// The scene depth
+    pub depth: DepthTexture2d,
+    /// The per-pixel screen space velocity since the last frame, if it was rendered
+    pub velocity: Option<Texture2d>,
+}
+
+/// Renders the scene drawn in the draw FnMut to the colour and depth textures
+pub fn render_to_texture<F, C>(
     context: &C,
-    target: &mut T,
+    target_color: &Texture2d,
+    target_depth: &DepthTexture2d,
     mut draw: F,
-) -> (Texture2d, DepthTexture2d)
-where
-    T: Surface,
+) where
     F: FnMut(&mut SimpleFrameBuffer),
     C: Facade + Clone,
 {
-    let target_dimensions = target.get_dimensions();
-
-    let target_color = Texture2d::empty(
-        context,
-        (target_dimensions.0 as f32 * system.downscale_factor) as u32,
-        (target_dimensions.1 as f32 * system.downscale_factor) as u32,
-    ).unwrap();
-
-    let target_depth = DepthTexture2d::empty_with_format(
-        context,
-        DepthFormat::F32,
-        MipmapsOption::NoMipmap,
-        (target_dimensions.0 as f32 * system.downscale_factor) as u32,
-        (target_dimensions.1 as f32 * system.downscale_factor) as u32,
-    ).unwrap();
-
     // first pass draw the scene into a buffer
-    draw(&mut SimpleFrameBuffer::with_depth_buffer(context, &target_color, &target_depth).unwrap());
+    draw(&mut SimpleFrameBuffer::with_depth_buffer(context, target_color, target_depth).unwrap());
+}
+
+/// The uniforms for a single post pass, layered over the uniforms shared by every pass
+struct PassUniforms<'a, U: 'a + Uniforms> {
+    /// Uniforms shared by every pass
+    shared: &'a U,
+    /// The post effect the options are passed from
+    system: &'a PostEffect,
+    /// The shaders and textures
+    shaders: &'a Shaders,
+    /// The output of the previous chained pass, or the scene for the first pass
+    tex: &'a Texture2d,
+    /// The scene colour before any passes
+    scene_tex: &'a Texture2d,
+    /// The scene depth
+    depth_buf: &'a DepthTexture2d,
+    /// The scene velocity
+    velocity: Option<&'a Texture2d>,
+    /// The outputs of the earlier passes with the pass names
+    outputs: &'a [(String, Texture2d)],
+    /// The outputs kept from the previous frame
+    history: &'a PostHistory,
+    /// The size of the pass render target
+    resolution: (f32, f32),
+    /// The pass specific uniforms
+    pass: &'a PostPass,
+}
+
+impl<'a, U: 'a + Uniforms> PassUniforms<'a, U> {
+    /// The output of the earlier pass with name
+    fn output(&self, name: &str) -> Option<&'a Texture2d> {
+        self.outputs.iter().find(|o| o.0 == name).map(|o| &o.1)
+    }
+}
+
+impl<'a, U: 'a + Uniforms> Uniforms for PassUniforms<'a, U> {
+    fn visit_values<'b, F: FnMut(&str, UniformValue<'b>)>(&'b self, mut f: F) {
+        self.shared.visit_values(&mut f);
+        self.system.post_shader_options.visit_values(&mut f);
+        self.system.tone_mapping.visit_values(&mut f);
+        self.system.bloom.visit_values(&mut f);
+        self.system.ssao.visit_values(&mut f);
+        self.system.anti_aliasing.visit_values(&mut f);
+        self.system.motion_blur.visit_values(&mut f);
+        self.system.color_blind.visit_values(&mut f);
+        f(
+            "auto_exposure",
+            UniformValue::Bool(
+                self.system.tone_mapping.auto_exposure && self.system.pass_active("adapt"),
+            ),
+        );
+        let tex = self
+            .pass
+            .inputs
+            .get("tex")
+            .and_then(|name| self.output(name))
+            .unwrap_or(self.tex);
+        f("tex", tex.as_uniform_value());
+        // grading blends from lut_tex to lut_target_tex
+        let grading = &self.system.color_grading;
+        let lut = self
+            .system
+            .blend_start
+            .as_ref()
+            .or_else(|| grading.lut.as_ref().and_then(|n| self.system.luts.get(n)));
+        let target_lut = grading
+            .target_lut
+            .as_ref()
+            .and_then(|n| self.system.luts.get(n))
+            .or(lut);
+        if let (Some(lut), Some(target_lut)) = (lut, target_lut) {
+            f("lut_tex", lut.texture.as_uniform_value());
+            f("lut_size", UniformValue::Float(lut.data.size as f32));
+            f("lut_domain_min", UniformValue::Vec3(lut.data.domain_min));
+            f("lut_domain_max", UniformValue::Vec3(lut.data.domain_max));
+            f("lut_target_tex", target_lut.texture.as_uniform_value());
+            f(
+                "lut_target_size",
+                UniformValue::Float(target_lut.data.size as f32),
+            );
+            f(
+                "lut_target_domain_min",
+                UniformValue::Vec3(target_lut.data.domain_min),
+            );
+            f(
+                "lut_target_domain_max",
+                UniformValue::Vec3(target_lut.data.domain_max),
+            );
+        } else {
+            f("lut_tex", self.scene_tex.as_uniform_value());
+            f("lut_size", UniformValue::Float(0f32));
+            f("lut_target_tex", self.scene_tex.as_uniform_value());
+            f("lut_target_size", UniformValue::Float(0f32));
+        }
+        f("lut_blend", grading.blend.as_uniform_value());
+        // the running transition and its wipe mask
+        match self.system.transition {
+            Some(ref transition) => {
+                transition.visit_values(&mut f);
+                let mask = match transition.options.transition_type {
+                    TransitionType::Wipe(ref name) => self.shaders.textures.get(name.as_str()),
+                    _ => None,
+                };
+                match mask {
+                    Some(mask) => f("transition_mask", mask.as_uniform_value()),
+                    None => f("transition_mask", self.scene_tex.as_uniform_value()),
+                }
+            }
+            None => {
+                f("transition_type", UniformValue::SignedInt(0));
+                f("transition_mask", self.scene_tex.as_uniform_value());
+            }
+        }
+        f("lut_amount", grading.amount.as_uniform_value());
+        f("scene_tex", self.scene_tex.as_uniform_value());
+        f("depth_buf", self.depth_buf.as_uniform_value());
+        f("has_velocity", UniformValue::Bool(self.velocity.is_some()));
+        f(
+            "velocity_tex",
+            self.velocity.unwrap_or(self.scene_tex).as_uniform_value(),
+        );
+        f("resolution", self.resolution.as_uniform_value());
+        for &(ref name, ref output) in self.outputs {
+            f(&format!("{}_tex", name), output.as_uniform_value());
+        }
+        for (name, pass_name) in self.pass.inputs.iter().filter(|&(n, _)| n != "tex") {
+            if let Some(output) = self.output(pass_name) {
+                f(name.as_str(), output.as_uniform_value());
+            }
+        }
+        // bind something to the history sampler on the first frame
+        let history_name = format!("{}_history", self.pass.name);
+        match self.history.textures.get(&history_name) {
+            Some(history) => {
+                f("has_history", UniformValue::Bool(true));
+                f(history_name.as_str(), history.as_uniform_value());
+            }
+            None => {
+                f("has_history", UniformValue::Bool(false));
+                f(history_name.as_str(), self.scene_tex.as_uniform_value());
+            }
+        }
+        for (name, value) in &self.pass.uniforms {
+            f(name.as_str(), value.as_uniform_value());
+        }
+    }
+}
+
+/// Runs the active post passes over a rendered scene colour and depth, with the last pass drawn
+/// to the viewport of target, and uniforms being passed to every pass
+pub fn render_post_passes<S, U>(
+    system: &PostEffect,
+    shaders: &Shaders,
+    target: &mut S,
+    viewport: Rect,
+    scene: &SceneTextures,
+    uniforms: &U,
+    history: &mut PostHistory,
+) where
+    S: Surface,
+    U: Uniforms,
+{
+    let (scene_color, scene_depth) = (&scene.color, &scene.depth);
+    let passes = system
+        .passes
+        .iter()
+        .filter(|p| p.active)
+        .collect::<Vec<_>>();
+    // the last pass draws straight to the screen, with no passes the scene is copied
+    let (last_pass, passes) = match passes.split_last() {
+        Some((last, passes)) => (Some(*last), passes),
+        None => (None, &passes[..]),
+    };
+    let mut outputs: Vec<(String, Texture2d)> = Vec::new();
+    // the index of the output read as tex by the next pass
+    let mut chained: Option<usize> = None;
+
+    let mut drawn = Vec::new();
+
+    for pass in passes {
+        // passes whose shader has since been removed are skipped
+        let program = match shaders.post_shaders.get(&pass.shader_name) {
+            Some(program) => program,
+            None => continue,
+        };
+
+        let output = {
+            let dimensions = pass.size.unwrap_or((
+                ((viewport.width as f32 * pass.scale) as u32).max(1),
+                ((viewport.height as f32 * pass.scale) as u32).max(1),
+            ));
+            let output = history.take_target(system, &pass.name, dimensions, system.hdr);
 
-    (target_color, target_depth)
+            let pass_uniforms = PassUniforms {
+                shared: uniforms,
+                system,
+                shaders,
+                tex: chained.map(|i| &outputs[i].1).unwrap_or(scene_color),
+                scene_tex: scene_color,
+                depth_buf: scene_depth,
+                velocity: scene.velocity.as_ref(),
+                outputs: &outputs,
+                history,
+                resolution: (dimensions.0 as f32, dimensions.1 as f32),
+                pass,
+            };
+
+            output
+                .as_surface()
+                .draw(
+                    &system.vertex_buffer,
+                    &system.index_buffer,
+                    program,
+                    &pass_uniforms,
+                    &Default::default(),
+                )
+                .unwrap();
+
+            output
+        };
+
+        outputs.push((pass.name.clone(), output));
+        drawn.push(*pass);
+        if pass.chained {
+            chained = Some(outputs.len() - 1);
+        }
+    }
+
+    {
+        let copy_pass = PostPass::default();
+        let pass = last_pass.unwrap_or(&copy_pass);
+        // falling back to copying if the last pass shader has since been removed
+        let program = last_pass
+            .and_then(|pass| shaders.post_shaders.get(&pass.shader_name))
+            .unwrap_or(&shaders.post_shaders["copy"]);
+
+        let pass_uniforms = PassUniforms {
+            shared: uniforms,
+            system,
+            shaders,
+            tex: chained.map(|i| &outputs[i].1).unwrap_or(scene_color),
+            scene_tex: scene_color,
+            depth_buf: scene_depth,
+            velocity: scene.velocity.as_ref(),
+            outputs: &outputs,
+            history,
+            resolution: (viewport.width as f32, viewport.height as f32),
+            pass,
+        };
+        let params = DrawParameters {
+            viewport: Some(viewport),
+            ..Default::default()
+        };
+
+        target
+            .draw(
+                &system.vertex_buffer,
+                &system.index_buffer,
+                program,
+                &pass_uniforms,
+                &params,
+            )
+            .unwrap();
+    }
+
+    history.frame += 1;
+
+    // keep the outputs of history passes for the next frame, the rest are reused as targets
+    for (pass, (_, output)) in drawn.into_iter().zip(outputs.into_iter()) {
+        let unused = if pass.history {
+            history
+                .textures
+                .insert(format!("{}_history", pass.name), output)
+        } else {
+            Some(output)
+        };
+        if let Some(unused) = unused {
+            history.keep_target(&pass.name, system.hdr, unused);
+        }
+    }
 }
@@ -0,0 +1,46 @@
+/// OpenGL 3.3 shaders for eye adaptation
+pub mod gl330 {
+    /// Eye adaptation fragment shader, outputs the adapted average scene luminance
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D scene_tex;
+        uniform sampler2D adapt_history;
+
+        uniform bool has_history;
+        uniform float delta;
+        uniform float adaptation_speed;
+        uniform float min_luminance;
+        uniform float max_luminance;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        const int grid_size = 16;
+
+        void main() {
+            // log average of the luminance over a grid of samples
+            float log_sum = 0.0;
+            for (int x = 0; x < grid_size; x++) {
+                for (int y = 0; y < grid_size; y++) {
+                    vec2 coord = (vec2(x, y) + 0.5) / float(grid_size);
+                    vec3 color = texture(scene_tex, coord).rgb;
+                    float lum = dot(color, vec3(0.2126, 0.7152, 0.0722));
+                    log_sum += log(max(lum, 0.0001));
+                }
+            }
+            float avg_lum = exp(log_sum / float(grid_size * grid_size));
+            avg_lum = clamp(avg_lum, min_luminance, max_luminance);
+
+            // move towards the new luminance over time
+            float adapted = avg_lum;
+            if (has_history) {
+                float last = texture(adapt_history, vec2(0.5)).r;
+                adapted = last + (avg_lum - last) * (1.0 - exp(-delta * adaptation_speed));
+            }
+
+            frag_output = vec4(adapted);
+        }
+    ";
+}
@@ -0,0 +1,102 @@
+/// OpenGL 3.3 shaders for bloom
+pub mod gl330 {
+    /// Bright pass fragment shader, keeps the parts of the image above the threshold
+    pub const BRIGHT_FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+
+        uniform float bloom_threshold;
+        uniform float bloom_soft_knee;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec3 color = texture(tex, v_tex_coords).rgb;
+            float brightness = max(color.r, max(color.g, color.b));
+
+            // quadratic curve easing in below the threshold
+            float knee = bloom_threshold * bloom_soft_knee + 0.0001;
+            float soft = clamp(brightness - bloom_threshold + knee, 0.0, 2.0 * knee);
+            soft = soft * soft / (4.0 * knee);
+            float contribution = max(soft, brightness - bloom_threshold) / max(brightness, 0.0001);
+
+            frag_output = vec4(color * contribution, 1.0);
+        }
+    ";
+
+    /// Downsample fragment shader, box filters the larger level to half size
+    pub const DOWNSAMPLE_FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec2 texel = 1.0 / vec2(textureSize(tex, 0));
+
+            vec3 color = texture(tex, v_tex_coords + texel * vec2(-1.0, -1.0)).rgb;
+            color += texture(tex, v_tex_coords + texel * vec2(1.0, -1.0)).rgb;
+            color += texture(tex, v_tex_coords + texel * vec2(-1.0, 1.0)).rgb;
+            color += texture(tex, v_tex_coords + texel * vec2(1.0, 1.0)).rgb;
+
+            frag_output = vec4(color * 0.25, 1.0);
+        }
+    ";
+
+    /// Upsample fragment shader, tent filters the smaller level and adds the level of the same size
+    pub const UPSAMPLE_FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+        uniform sampler2D base_tex;
+
+        uniform float bloom_radius;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec2 texel = bloom_radius / vec2(textureSize(tex, 0));
+
+            vec3 color = texture(tex, v_tex_coords).rgb * 4.0;
+            color += texture(tex, v_tex_coords + texel * vec2(-1.0, 0.0)).rgb * 2.0;
+            color += texture(tex, v_tex_coords + texel * vec2(1.0, 0.0)).rgb * 2.0;
+            color += texture(tex, v_tex_coords + texel * vec2(0.0, -1.0)).rgb * 2.0;
+            color += texture(tex, v_tex_coords + texel * vec2(0.0, 1.0)).rgb * 2.0;
+            color += texture(tex, v_tex_coords + texel * vec2(-1.0, -1.0)).rgb;
+            color += texture(tex, v_tex_coords + texel * vec2(1.0, -1.0)).rgb;
+            color += texture(tex, v_tex_coords + texel * vec2(-1.0, 1.0)).rgb;
+            color += texture(tex, v_tex_coords + texel * vec2(1.0, 1.0)).rgb;
+
+            frag_output = vec4(color / 16.0 + texture(base_tex, v_tex_coords).rgb, 1.0);
+        }
+    ";
+
+    /// Composite fragment shader, adds the blurred bloom to the scene
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+        uniform sampler2D bloom_tex;
+
+        uniform float bloom_intensity;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec4 color = texture(tex, v_tex_coords);
+            vec3 bloom = texture(bloom_tex, v_tex_coords).rgb;
+
+            frag_output = vec4(color.rgb + bloom * bloom_intensity, color.a);
+        }
+    ";
+}
@@ -0,0 +1,215 @@
+/// OpenGL 3.3 GLSL chunks that shaders can #include by name
+pub mod gl330 {
+    /// Quaternion maths for rotating and placing instances, included as "quaternion"
+    pub const QUATERNION: &str = "
+        // rotates v by the quaternion q
+        vec3 rotate_vec3(vec4 q, vec3 v) {
+            vec3 temp = cross(q.xyz, v) + q.w * v;
+            return v + 2.0 * cross(q.xyz, temp);
+        }
+
+        // applies the scale, rotation and translation of an instance to a position
+        vec3 transform_instance(vec3 pos, vec3 translation, vec4 rotation, vec3 scale) {
+            return rotate_vec3(rotation, pos * scale) + translation;
+        }
+
+        // applies the scale and rotation of an instance to a normal, keeping it at right
+        // angles to the scaled surface
+        vec3 transform_normal(vec3 normal, vec4 rotation, vec3 scale) {
+            return normalize(rotate_vec3(rotation, normal / scale));
+        }
+    ";
+
+    /// The material's tessellation levels for tessellation control shaders, included as
+    /// "tessellation_levels"
+    pub const TESSELLATION_LEVELS: &str = "
+        uniform vec3 cam_pos;
+        uniform float tess_inner_level;
+        uniform float tess_outer_level;
+        uniform bool tess_adaptive;
+        uniform float tess_near;
+        uniform float tess_far;
+
+        // scales a level down to 1 between the near and far distance
+        float adapt_level(float level, vec3 pos) {
+            if (!tess_adaptive) {
+                return level;
+            }
+            float t = clamp((distance(cam_pos, pos) - tess_near) / max(tess_far - tess_near, 0.0001),
+                0.0, 1.0);
+            return mix(level, 1.0, t);
+        }
+
+        // sets the levels of the patch, edges use their midpoints so neighbouring triangles
+        // match without cracks
+        void set_tess_levels() {
+            vec3 p0 = gl_in[0].gl_Position.xyz;
+            vec3 p1 = gl_in[1].gl_Position.xyz;
+            vec3 p2 = gl_in[2].gl_Position.xyz;
+
+            gl_TessLevelOuter[0] = adapt_level(tess_outer_level, (p1 + p2) * 0.5);
+            gl_TessLevelOuter[1] = adapt_level(tess_outer_level, (p2 + p0) * 0.5);
+            gl_TessLevelOuter[2] = adapt_level(tess_outer_level, (p0 + p1) * 0.5);
+            gl_TessLevelInner[0] = adapt_level(tess_inner_level, (p0 + p1 + p2) / 3.0);
+        }
+    ";
+
+    /// Interpolation across a tessellated triangle for tessellation evaluation shaders,
+    /// included as "tessellation"
+    pub const TESSELLATION: &str = "
+        // interpolates a vec3 of the patch's vertices at the tessellation coordinate
+        vec3 tess_calc(vec3 one, vec3 two, vec3 three) {
+            return (gl_TessCoord.x * one) + (gl_TessCoord.y * two) + (gl_TessCoord.z * three);
+        }
+
+        // interpolates a vec2 of the patch's vertices at the tessellation coordinate
+        vec2 tex_calc(vec2 one, vec2 two, vec2 three) {
+            return (gl_TessCoord.x * one) + (gl_TessCoord.y * two) + (gl_TessCoord.z * three);
+        }
+    ";
+
+    /// Directional lighting from the row of lights in dir_lights, included as "lighting"
+    pub const LIGHTING: &str = "
+        // sums the diffuse light from each of the directional lights
+        float dir_light_lum(sampler2D lights, vec3 normal) {
+            int size = textureSize(lights, 0).x;
+            float lum = 0.0;
+            for (int i = 0; i < size; i++) {
+                vec3 light_norm = normalize(texelFetch(lights, ivec2(i, 0), 0).xyz);
+                lum += max(dot(normalize(normal), light_norm), 0.0);
+            }
+            return lum;
+        }
+
+        // sums the diffuse light from each of the directional lights tinted by its colour
+        vec3 dir_light_color(sampler2D lights, sampler2D colors, vec3 normal) {
+            int size = textureSize(lights, 0).x;
+            vec3 color = vec3(0.0);
+            for (int i = 0; i < size; i++) {
+                vec3 light_norm = normalize(texelFetch(lights, ivec2(i, 0), 0).xyz);
+                vec3 light_color = texelFetch(colors, ivec2(i, 0), 0).rgb;
+                color += max(dot(normalize(normal), light_norm), 0.0) * light_color;
+            }
+            return color;
+        }
+    ";
+
+    /// Distance fog, included as "fog"
+    pub const FOG: &str = "
+        // the fog modes, these must match FogMode::mode_index in fog.rs
+        #define FOG_LINEAR 0
+        #define FOG_EXPONENTIAL 1
+        #define FOG_EXPONENTIAL_SQUARED 2
+
+        // how much of the fog colour shows at a distance, with exponential squared falloff
+        float fog_amount(float dist, float density) {
+            float d = dist * density;
+            return 1.0 - clamp(exp(-d * d), 0.0, 1.0);
+        }
+
+        // blends a colour towards the fog colour by distance
+        vec3 apply_fog(vec3 color, vec3 fog_color, float dist, float density) {
+            return mix(color, fog_color, fog_amount(dist, density));
+        }
+
+        // how much fog there is past the start distance, linear fog is opaque at the end distance
+        float fog_mode_amount(int mode, float dist, float start, float end, float density) {
+            float d = max(dist - start, 0.0);
+            if (mode == FOG_LINEAR) {
+                return clamp(d / max(end - start, 0.0001), 0.0, 1.0);
+            } else if (mode == FOG_EXPONENTIAL) {
+                return 1.0 - clamp(exp(-d * density), 0.0, 1.0);
+            }
+            return fog_amount(d, density);
+        }
+
+        // thins fog out above y = 0
+        float fog_height_amount(float amount, float height, float height_falloff) {
+            return amount * exp(-height_falloff * max(height, 0.0));
+        }
+    ";
+
+    /// The renderer's fog settings and applying them, included as "scene_fog"
+    pub const SCENE_FOG: &str = "
+        uniform bool fog_active;
+        uniform int fog_mode;
+        uniform vec3 fog_color;
+        uniform float fog_density;
+        uniform float fog_start;
+        uniform float fog_end;
+        uniform float fog_height_falloff;
+
+        #include \"fog\"
+
+        // how much of the fog colour shows at a position seen from the camera
+        float scene_fog_amount(vec3 cam_pos, vec3 pos) {
+            if (!fog_active) {
+                return 0.0;
+            }
+            float amount =
+                fog_mode_amount(fog_mode, distance(cam_pos, pos), fog_start, fog_end, fog_density);
+            return fog_height_amount(amount, pos.y, fog_height_falloff);
+        }
+
+        // blends a colour towards the fog colour for a position seen from the camera
+        vec3 apply_scene_fog(vec3 color, vec3 cam_pos, vec3 pos) {
+            return mix(color, fog_color, scene_fog_amount(cam_pos, pos));
+        }
+    ";
+
+    /// Conversions between linear and sRGB encoded colour, included as "srgb"
+    pub const SRGB: &str = "
+        // encodes a linear colour with the sRGB transfer curve
+        vec3 linear_to_srgb(vec3 color) {
+            color = clamp(color, 0.0, 1.0);
+            return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055,
+                step(vec3(0.0031308), color));
+        }
+
+        // decodes an sRGB encoded colour to linear
+        vec3 srgb_to_linear(vec3 color) {
+            return mix(color / 12.92, pow((color + 0.055) / 1.055, vec3(2.4)),
+                step(vec3(0.04045), color));
+        }
+    ";
+
+    /// Value noise and fractal noise, included as "noise"
+    pub const NOISE: &str = "
+        // pseudo random value from 0 to 1 for a position
+        float hash(vec3 p) {
+            p = fract(p * 0.3183099 + 0.1);
+            p *= 17.0;
+            return fract(p.x * p.y * p.z * (p.x + p.y + p.z));
+        }
+
+        // smooth value noise from 0 to 1
+        float noise(vec3 p) {
+            vec3 i = floor(p);
+            vec3 f = fract(p);
+            f = f * f * (3.0 - 2.0 * f);
+
+            return mix(
+                mix(mix(hash(i + vec3(0.0, 0.0, 0.0)), hash(i + vec3(1.0, 0.0, 0.0)), f.x),
+                    mix(hash(i + vec3(0.0, 1.0, 0.0)), hash(i + vec3(1.0, 1.0, 0.0)), f.x),
+                    f.y),
+                mix(mix(hash(i + vec3(0.0, 0.0, 1.0)), hash(i + vec3(1.0, 0.0, 1.0)), f.x),
+                    mix(hash(i + vec3(0.0, 1.0, 1.0)), hash(i + vec3(1.0, 1.0, 1.0)), f.x),
+                    f.y),
+                f.z);
+        }
+
+        // fractal noise from 0 to 1, summing octaves of value noise
+        float fbm(vec3 p, int octaves) {
+            float value = 0.0;
+            float amplitude = 0.5;
+            float total = 0.0;
+            for (int i = 0; i < octaves; i++) {
+                value += noise(p) * amplitude;
+                total += amplitude;
+                p *= 2.0;
+                amplitude *= 0.5;
+            }
+            return value / max(total, 0.0001);
+        }
+    ";
+}
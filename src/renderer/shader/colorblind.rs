@@ -0,0 +1,66 @@
+/// OpenGL 3.3 shaders for colour blindness correction and simulation
+pub mod gl330 {
+    /// Colour blindness fragment shader, daltonizes or simulates protanopia, deuteranopia or
+    /// tritanopia in LMS colour space
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+
+        uniform int color_blind_deficiency;
+        uniform bool color_blind_simulate;
+        uniform float color_blind_strength;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        vec3 rgb_to_lms(vec3 c) {
+            return vec3(
+                dot(c, vec3(17.8824, 43.5161, 4.11935)),
+                dot(c, vec3(3.45565, 27.1554, 3.86714)),
+                dot(c, vec3(0.0299566, 0.184309, 1.46709)));
+        }
+
+        vec3 lms_to_rgb(vec3 c) {
+            return vec3(
+                dot(c, vec3(0.0809444479, -0.130504409, 0.116721066)),
+                dot(c, vec3(-0.0102485335, 0.0540193266, -0.113614708)),
+                dot(c, vec3(-0.000365296938, -0.00412161469, 0.693511405)));
+        }
+
+        // the colour as seen without the missing cone type
+        vec3 simulate(vec3 color) {
+            vec3 lms = rgb_to_lms(color);
+            if (color_blind_deficiency == 1) {
+                lms.x = 2.02344 * lms.y - 2.52581 * lms.z;
+            } else if (color_blind_deficiency == 2) {
+                lms.y = 0.494207 * lms.x + 1.24827 * lms.z;
+            } else if (color_blind_deficiency == 3) {
+                lms.z = -0.395913 * lms.x + 0.801109 * lms.y;
+            }
+            return lms_to_rgb(lms);
+        }
+
+        void main() {
+            vec4 color = texture(tex, v_tex_coords);
+            vec3 simulated = simulate(color.rgb);
+
+            vec3 filtered;
+            if (color_blind_simulate) {
+                filtered = simulated;
+            } else {
+                // shift the lost information into the channels that can still be seen
+                vec3 error = color.rgb - simulated;
+                vec3 shift = vec3(
+                    0.0,
+                    0.7 * error.r + error.g,
+                    0.7 * error.r + error.b);
+                filtered = color.rgb + shift;
+            }
+
+            filtered = clamp(filtered, 0.0, 1.0);
+            frag_output = vec4(mix(color.rgb, filtered, color_blind_strength), color.a);
+        }
+    ";
+}
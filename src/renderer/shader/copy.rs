@@ -0,0 +1,17 @@
+/// OpenGL 3.3 shaders for copying without any post processing
+pub mod gl330 {
+    /// Copy fragment shader that outputs the input texture unchanged
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        void main() {
+            frag_output = texture(tex, v_tex_coords);
+        }
+    ";
+}
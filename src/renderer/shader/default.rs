@@ -11,24 +11,60 @@ pub mod gl330 {
         layout(location = 4) in vec3 world_scale;
         layout(location = 5) in vec2 texture;
 
+        #include \"quaternion\"
+
         out vec3 v_normal;
         out vec2 v_texture;
 
         void main() {
-            vec3 pos_scaled = position * world_scale;
-
-            vec3 temp = cross(world_rotation.xyz, pos_scaled) + world_rotation.w * pos_scaled;
-            vec3 pos_rotated = pos_scaled + 2.0 * cross(world_rotation.xyz, temp);
-
-            vec3 pos_final = pos_rotated + world_position;
+            vec3 pos_final = transform_instance(position, world_position, world_rotation, world_scale);
 
             gl_Position = vec4(pos_final, 1.0);
 
-            v_normal = normal;
+            v_normal = transform_normal(normal, world_rotation, world_scale);
             v_texture = texture;
         }
     ";
 
+    /// Vertex shader for drawing without tessellation, such as lines and points, outputs
+    /// straight to the fragment shader, or to a geometry shader when GEOMETRY is defined
+    pub const DIRECT_VERT: &str = "
+        #version 330
+
+        uniform mat4 projection_matrix;
+        uniform mat4 modelview_matrix;
+
+        layout(location = 0) in vec3 position;
+        layout(location = 1) in vec3 normal;
+        layout(location = 2) in vec3 world_position;
+        layout(location = 3) in vec4 world_rotation;
+        layout(location = 4) in vec3 world_scale;
+        layout(location = 5) in vec2 texture;
+
+        #include \"quaternion\"
+
+        // geometry shaders take the outputs of tessellation
+        #ifdef GEOMETRY
+        #define g_normal te_normal
+        #define g_pos te_pos
+        #define g_texture te_texture
+        #endif
+
+        out vec3 g_normal;
+        out vec3 g_pos;
+        out vec2 g_texture;
+
+        void main() {
+            vec3 pos_final = transform_instance(position, world_position, world_rotation, world_scale);
+
+            gl_Position = projection_matrix * modelview_matrix * vec4(pos_final, 1.0);
+
+            g_normal = transform_normal(normal, world_rotation, world_scale);
+            g_pos = pos_final;
+            g_texture = texture;
+        }
+    ";
+
     /// Default geometry shader
     pub const GEOM: &str = "
         #version 330
@@ -56,38 +92,40 @@ pub mod gl330 {
         }
     ";
 
-    /// Default tessellation control shader
+    /// Default tessellation control shader, levels fall off with distance when adaptive
     pub const TESS_CONTROL: &str = "
         #version 400
 
         layout(vertices = 3) out;
 
+        #include \"tessellation_levels\"
+
         in vec3 v_normal[];
         in vec2 v_texture[];
 
         out vec3 tc_normal[];
         out vec2 tc_texture[];
 
-        const float tess_level = 1.0;
-
         void main() {
             tc_normal[gl_InvocationID] = v_normal[gl_InvocationID];
             tc_texture[gl_InvocationID] = v_texture[gl_InvocationID];
             gl_out[gl_InvocationID].gl_Position = gl_in[gl_InvocationID].gl_Position;
 
-            gl_TessLevelOuter[0] = tess_level;
-            gl_TessLevelOuter[1] = tess_level;
-            gl_TessLevelOuter[2] = tess_level;
-            gl_TessLevelInner[0] = tess_level;
+            if (gl_InvocationID == 0) {
+                set_tess_levels();
+            }
         }
     ";
 
-    /// Default tessellation evaluation shader
+    /// Default tessellation evaluation shader, displaces along the normal with a displacement map
     pub const TESS_EVAL: &str = "
         #version 400
 
         uniform mat4 projection_matrix;
         uniform mat4 modelview_matrix;
+        uniform sampler2D displacement_tex;
+        uniform bool has_displacement;
+        uniform float displacement_scale;
 
         layout(triangles, equal_spacing, ccw) in;
 
@@ -98,17 +136,7 @@ pub mod gl330 {
         out vec3 te_pos;
         out vec2 te_texture;
 
-        vec3 tess_calc (vec3 one, vec3 two, vec3 three) {
-            return ((gl_TessCoord.x) * one) +
-                            ((gl_TessCoord.y) * two) +
-                            ((gl_TessCoord.z) * three);
-        }
-
-        vec2 tex_calc (vec2 one, vec2 two, vec2 three) {
-            return ((gl_TessCoord.x) * one) +
-                            ((gl_TessCoord.y) * two) +
-                            ((gl_TessCoord.z) * three);
-        }
+        #include \"tessellation\"
 
         void main () {
             te_normal = tess_calc(tc_normal[0], tc_normal[1], tc_normal[2]);
@@ -117,10 +145,16 @@ pub mod gl330 {
                 gl_in[1].gl_Position.xyz,
                 gl_in[2].gl_Position.xyz);
 
-            te_pos = position;
+            vec2 tex_coords = tex_calc(tc_texture[0], tc_texture[1], tc_texture[2]);
+            te_texture = tex_coords;
+
+            // push the new vertices out along the normal by the displacement map
+            if (has_displacement) {
+                float height = texture(displacement_tex, tex_coords).r;
+                position += normalize(te_normal) * height * displacement_scale;
+            }
 
-            vec2 texture = tex_calc(tc_texture[0], tc_texture[1], tc_texture[2]);
-            te_texture = texture;
+            te_pos = position;
 
             gl_Position = projection_matrix *
                 modelview_matrix *
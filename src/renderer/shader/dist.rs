@@ -1,11 +1,16 @@
 /// OpenGL 3.3 shaders for distance roll off
 pub mod gl330 {
-    /// Distance fragment shader that rolls off to white the further from the camera
+    /// Distance fragment shader that fades into the fog the further from the camera
     pub const FRAG: &str = "
         #version 330
 
         uniform vec3 cam_pos;
-        uniform sampler1D dir_lights;
+        uniform sampler2D dir_lights;
+        uniform sampler2D dir_light_colors;
+        uniform vec3 ambient_color;
+
+        #include \"lighting\"
+        #include \"scene_fog\"
 
         in vec3 g_normal;
         in vec3 g_pos;
@@ -13,17 +18,10 @@ pub mod gl330 {
         out vec4 frag_output;
 
         void main() {
-            int size = textureSize(dir_lights, 0);
-            float lum = 0.0;
-            for (int i = 0; i < size; i++) {
-                vec3 light_norm = normalize(texture(dir_lights, i).xyz);
-                lum += max(dot(normalize(g_normal), light_norm), 0.0);
-            }
-
-            float dist = abs(distance(cam_pos, g_pos)) / 80.0;
+            vec3 light = dir_light_color(dir_lights, dir_light_colors, g_normal);
 
-            vec3 color = vec3(0.3 + (0.2 * lum) + (0.5 * dist));
-            frag_output = vec4(color, 1.0);
+            vec3 color = (0.3 * ambient_color) + (0.2 * light);
+            frag_output = vec4(apply_scene_fog(color, cam_pos, g_pos), 1.0);
         }
     ";
 }
@@ -0,0 +1,58 @@
+/// OpenGL 3.3 shaders for fast approximate anti-aliasing
+pub mod gl330 {
+    /// FXAA fragment shader, blurs along edges found from the luma of the neighbours
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+
+        uniform float fxaa_span_max;
+        uniform float fxaa_reduce_mul;
+        uniform float fxaa_reduce_min;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        const vec3 luma = vec3(0.299, 0.587, 0.114);
+
+        void main() {
+            vec2 texel = 1.0 / vec2(textureSize(tex, 0));
+
+            vec4 color = texture(tex, v_tex_coords);
+            float luma_m = dot(color.rgb, luma);
+            float luma_nw = dot(texture(tex, v_tex_coords + vec2(-1.0, -1.0) * texel).rgb, luma);
+            float luma_ne = dot(texture(tex, v_tex_coords + vec2(1.0, -1.0) * texel).rgb, luma);
+            float luma_sw = dot(texture(tex, v_tex_coords + vec2(-1.0, 1.0) * texel).rgb, luma);
+            float luma_se = dot(texture(tex, v_tex_coords + vec2(1.0, 1.0) * texel).rgb, luma);
+
+            float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+            float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+            // direction along the edge
+            vec2 dir = vec2(
+                -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+                (luma_nw + luma_sw) - (luma_ne + luma_se));
+            float dir_reduce = max(
+                (luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * fxaa_reduce_mul,
+                fxaa_reduce_min);
+            float rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+            dir = clamp(dir * rcp_dir_min, vec2(-fxaa_span_max), vec2(fxaa_span_max)) * texel;
+
+            vec3 rgb_a = 0.5 * (
+                texture(tex, v_tex_coords + dir * (1.0 / 3.0 - 0.5)).rgb +
+                texture(tex, v_tex_coords + dir * (2.0 / 3.0 - 0.5)).rgb);
+            vec3 rgb_b = rgb_a * 0.5 + 0.25 * (
+                texture(tex, v_tex_coords + dir * -0.5).rgb +
+                texture(tex, v_tex_coords + dir * 0.5).rgb);
+
+            // the wider sample can stray over another edge
+            float luma_b = dot(rgb_b, luma);
+            if (luma_b < luma_min || luma_b > luma_max) {
+                frag_output = vec4(rgb_a, color.a);
+            } else {
+                frag_output = vec4(rgb_b, color.a);
+            }
+        }
+    ";
+}
@@ -0,0 +1,61 @@
+/// OpenGL 3.3 shaders for colour grading
+pub mod gl330 {
+    /// Colour grading fragment shader, looks colours up in a blend of two 3D LUT strips,
+    /// which map sRGB encoded colours as .cube files do
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+        uniform sampler2D lut_tex;
+        uniform sampler2D lut_target_tex;
+
+        uniform float lut_size;
+        uniform float lut_target_size;
+        uniform vec3 lut_domain_min;
+        uniform vec3 lut_domain_max;
+        uniform vec3 lut_target_domain_min;
+        uniform vec3 lut_target_domain_max;
+        uniform float lut_blend;
+        uniform float lut_amount;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        #include \"srgb\"
+
+        // look up a colour in a strip of size slices covering the domain of input colours,
+        // interpolating between the blue slices
+        vec3 apply_lut(sampler2D lut, float size, vec3 domain_min, vec3 domain_max, vec3 color) {
+            color = clamp((color - domain_min) / (domain_max - domain_min), 0.0, 1.0);
+            float slice = color.b * (size - 1.0);
+            float slice_0 = floor(slice);
+            float slice_1 = min(slice_0 + 1.0, size - 1.0);
+
+            float x = (color.r * (size - 1.0) + 0.5) / (size * size);
+            float y = (color.g * (size - 1.0) + 0.5) / size;
+
+            vec3 color_0 = texture(lut, vec2(x + slice_0 / size, y)).rgb;
+            vec3 color_1 = texture(lut, vec2(x + slice_1 / size, y)).rgb;
+
+            return mix(color_0, color_1, slice - slice_0);
+        }
+
+        void main() {
+            vec4 color = texture(tex, v_tex_coords);
+            if (lut_size < 2.0) {
+                frag_output = color;
+                return;
+            }
+
+            // the scene is linear, the tables are looked up with and return sRGB colours
+            vec3 encoded = linear_to_srgb(color.rgb);
+            vec3 graded = apply_lut(lut_tex, lut_size, lut_domain_min, lut_domain_max, encoded);
+            vec3 target = apply_lut(lut_target_tex, lut_target_size,
+                lut_target_domain_min, lut_target_domain_max, encoded);
+            graded = srgb_to_linear(mix(graded, target, lut_blend));
+
+            frag_output = vec4(mix(color.rgb, graded, lut_amount), color.a);
+        }
+    ";
+}
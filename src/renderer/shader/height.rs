@@ -1,12 +1,15 @@
 /// OpenGL 3.3 shaders with height roll off
 pub mod gl330 {
-    /// Height fragment shader that rolls off to white in relation to the y-axis
+    /// Height fragment shader shaded along the y-axis that fades into the fog with distance
     pub const FRAG: &str = "
         #version 330
 
         uniform vec3 cam_pos;
+        uniform vec3 ambient_color;
         const vec3 LIGHT = vec3(-0.2, 0.8, 0.1);
 
+        #include \"scene_fog\"
+
         in vec3 g_normal;
         in vec3 g_pos;
 
@@ -14,14 +17,12 @@ pub mod gl330 {
 
         void main() {
             float lum = max(dot(normalize(g_normal), normalize(LIGHT)), 0.0);
-            float dist = abs(distance(cam_pos, g_pos)) / 80;
 
             float col_val = normalize(g_pos).y;
-            vec3 base_color = vec3(col_val);
-            base_color += dist;
+            vec3 base_color = vec3(col_val) * ambient_color;
 
-            vec3 color = base_color * ((0.2 * lum) + (0.8 * dist));
-            frag_output = vec4(color, 1.0);
+            vec3 color = base_color * (0.5 + (0.5 * lum));
+            frag_output = vec4(apply_scene_fog(color, cam_pos, g_pos), 1.0);
         }
     ";
 }
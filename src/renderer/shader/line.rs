@@ -4,8 +4,11 @@ pub mod gl330 {
     pub const FRAG: &str = "
         #version 330
 
+        uniform vec3 cam_pos;
         const vec3 LIGHT = vec3(-0.2, 0.8, 0.1);
 
+        #include \"scene_fog\"
+
         in vec3 g_normal;
         in vec3 g_pos;
 
@@ -18,7 +21,7 @@ pub mod gl330 {
             vec3 base_color = vec3(1.0, 0.0, 0.0);
 
             vec3 color = base_color * (0.3 + (0.2 * lum) + (0.5 * dist));
-            frag_output = vec4(color, 1.0);
+            frag_output = vec4(apply_scene_fog(color, cam_pos, g_pos), 1.0);
         }
     ";
 
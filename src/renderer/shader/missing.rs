@@ -0,0 +1,18 @@
+/// OpenGL 3.3 shaders for render items whose material is missing a shader
+pub mod gl330 {
+    /// Missing fragment shader, magenta and black checks in world space so it always shows
+    pub const FRAG: &str = "
+        #version 330
+
+        in vec3 g_normal;
+        in vec3 g_pos;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec3 cell = floor(g_pos * 2.0);
+            float check = mod(cell.x + cell.y + cell.z, 2.0);
+            frag_output = vec4(mix(vec3(1.0, 0.0, 1.0), vec3(0.0), check), 1.0);
+        }
+    ";
+}
@@ -1,167 +1,825 @@
+/// Eye adaptation post shader for measuring average scene luminance
+pub mod adapt;
+/// Bloom post shaders for bright pass, blurring and compositing
+pub mod bloom;
+/// Shared GLSL chunks that shaders can include
+pub mod chunks;
+/// Colour blindness post shader for daltonizing and simulation
+pub mod colorblind;
+/// Copy post shader for passing a texture through unchanged
+pub mod copy;
 /// Default shaders
 pub mod default;
 /// Distance shaders for roll off to white in relation to the camera position
 pub mod dist;
+/// Fast approximate anti-aliasing post shader
+pub mod fxaa;
+/// Colour grading post shader using 3D lookup tables
+pub mod grading;
 /// Height shaders for rolling of to white in relation to the y-axis
 pub mod height;
 /// Line shaders for rendering wireframes
 pub mod line;
+/// Missing shader drawn when a material's shader does not exist
+pub mod missing;
+/// Motion blur post shader
+pub mod motionblur;
 /// Default post effect shader
 pub mod post;
+/// Reflective shaders for mirroring the environment map
+pub mod reflect;
+/// Procedural sky shader lit by the sun
+pub mod sky;
+/// Skybox shaders for drawing the environment cubemap behind the scene
+pub mod skybox;
+/// Screen space ambient occlusion post shaders
+pub mod ssao;
+/// Temporal anti-aliasing post shader
+pub mod taa;
 /// Shader for rendering with textures
 pub mod texture;
+/// Tone mapping post shader for displaying HDR scenes
+pub mod tonemap;
+/// Transition post shader for fades, crossfades and wipes
+pub mod transition;
+/// Velocity shaders for rendering the movement of render items since the last frame
+pub mod velocity;
 
+use glium::program::SourceCode;
 use glium::texture::compressed_srgb_texture2d::CompressedSrgbTexture2d;
-use glium::{Display, Program};
+use glium::texture::{RawImage2d, Texture2d};
+use glium::{Api, Display, Program, Version};
 use std::collections::HashMap;
-use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use time;
+use types::{Material, Topology};
+
+/// Denotes which map a shader loaded from files is kept in
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ShaderTarget {
+    /// The shaders used to render RenderItems as triangles, tessellated when supported
+    Scene,
+    /// The shaders used to render RenderItems without tessellation
+    Direct,
+    /// The post processing shaders
+    Post,
+}
+
+/// A shader loaded from files, recompiled when any of them change
+struct ShaderFiles {
+    /// The name the shader is registered under
+    name: String,
+    /// The map the shader is kept in
+    target: ShaderTarget,
+    /// The vertex and fragment stages, then geometry and tessellation for Scene shaders
+    paths: Vec<PathBuf>,
+    /// When each file was last modified at the last compile
+    modified: Vec<Option<SystemTime>>,
+}
 
 /// Contains all the shaders to be used
 pub struct Shaders {
-    /// Map containing all the shaders used to render the RenderItems
-    pub shaders: HashMap<&'static str, Program>,
+    /// Map containing all the shaders used to render the RenderItems as triangles, empty when
+    /// the context has neither tessellation nor geometry shaders
+    pub shaders: HashMap<String, Program>,
+    /// Map containing the same shaders without tessellation or geometry stages, for lines and
+    /// points and for triangles when the context has neither
+    pub direct_shaders: HashMap<String, Program>,
     /// Map containing all the shaders to be used for post processing
-    pub post_shaders: HashMap<&'static str, Program>,
+    pub post_shaders: HashMap<String, Program>,
     /// Texture that can be passed to the shaders
-    pub textures: HashMap<&'static str, CompressedSrgbTexture2d>,
+    pub textures: HashMap<String, CompressedSrgbTexture2d>,
+    /// Textures of linear data that can be passed to the shaders, such as displacement maps,
+    /// sampled without sRGB decoding
+    pub data_textures: HashMap<String, Texture2d>,
+    /// Whether the context supports tessellation, otherwise triangles are drawn through the
+    /// geometry stage of the shaders without it
+    pub tessellation: bool,
+    /// Whether the context supports geometry shaders, otherwise everything is drawn direct
+    pub geometry: bool,
+    /// Whether the context is OpenGL ES, which shaders are translated to when compiled
+    pub gles: bool,
+    /// Named chunks of GLSL that shaders can #include
+    pub chunks: HashMap<String, String>,
+    /// The last compile error of each shader loaded from files by its name and target,
+    /// shown in the editor
+    pub errors: HashMap<(String, ShaderTarget), String>,
+    /// How many seconds to wait between checking the watched files for changes
+    pub reload_interval: f64,
+    /// When the watched files were last checked
+    last_reload: f64,
+    /// The program for the velocity of tessellated triangles, when tessellation is supported
+    velocity: Option<Program>,
+    /// The program for the velocity of everything drawn without tessellation
+    velocity_direct: Program,
+    /// The program for the procedural sky
+    sky: Program,
+    /// The program for the skybox
+    skybox: Program,
+    /// The shaders loaded from files that are watched for changes
+    files: Vec<ShaderFiles>,
 }
 
 impl Shaders {
     /// Creates a new instance of Shaders
     pub fn new(display: &Display) -> Shaders {
-        let mut shaders = HashMap::new();
-
-        // the shader programs
-        shaders.insert(
-            "dist",
-            program!(display,
-                     330 => {
-                         vertex: default::gl330::VERT,
-                         fragment: dist::gl330::FRAG,
-                         geometry: default::gl330::GEOM,
-                         tessellation_control: default::gl330::TESS_CONTROL,
-                         tessellation_evaluation: default::gl330::TESS_EVAL
-                     }).unwrap(),
-        );
+        let version = *display.get_opengl_version();
+        let gles = version.0 == Api::GlEs;
 
-        shaders.insert(
-            "height",
-            program!(display,
-                     330 => {
-                         vertex: default::gl330::VERT,
-                         fragment: height::gl330::FRAG,
-                         geometry: default::gl330::GEOM,
-                         tessellation_control: default::gl330::TESS_CONTROL,
-                         tessellation_evaluation: default::gl330::TESS_EVAL
-                     }).unwrap(),
-        );
+        // geometry shaders came with GL 3.2 and tessellation with 4.0, GLES 3.0 has neither
+        let tessellation = !gles && version >= Version(Api::Gl, 4, 0);
+        let geometry = !gles && version >= Version(Api::Gl, 3, 2);
 
-        shaders.insert(
-            "line",
-            program!(display,
-                     330 => {
-                         vertex: default::gl330::VERT,
-                         fragment: line::gl330::FRAG,
-                         geometry: line::gl330::GEOM,
-                         tessellation_control: default::gl330::TESS_CONTROL,
-                         tessellation_evaluation: default::gl330::TESS_EVAL
-                     }).unwrap(),
-        );
+        // the chunks shaders can include
+        let mut chunks = HashMap::new();
+        let builtin_chunks = [
+            ("quaternion", chunks::gl330::QUATERNION),
+            ("lighting", chunks::gl330::LIGHTING),
+            ("fog", chunks::gl330::FOG),
+            ("scene_fog", chunks::gl330::SCENE_FOG),
+            ("noise", chunks::gl330::NOISE),
+            ("srgb", chunks::gl330::SRGB),
+            ("tessellation_levels", chunks::gl330::TESSELLATION_LEVELS),
+            ("tessellation", chunks::gl330::TESSELLATION),
+        ];
+        for &(name, source) in builtin_chunks.iter() {
+            chunks.insert(name.to_string(), source.to_string());
+        }
 
-        // the shader programs
-        shaders.insert(
-            "texture",
-            program!(display,
-                     330 => {
-                         vertex: default::gl330::VERT,
-                         fragment: texture::gl330::FRAG,
-                         geometry: default::gl330::GEOM,
-                         tessellation_control:
-                             default::gl330::TESS_CONTROL,
-                             tessellation_evaluation:
-                                 default::gl330::TESS_EVAL
-                     }).unwrap(),
-        );
+        // the programs the renderer draws with itself, kept out of the maps materials use
+        let compile = |stages: &[&str]| {
+            stages
+                .iter()
+                .map(|s| preprocess(&chunks, s, &[]))
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|sources| compile_sources(display, gles, &sources))
+                .unwrap()
+        };
+        let velocity = if tessellation {
+            Some(compile(&[
+                velocity::gl330::TESS_VERT,
+                velocity::gl330::FRAG,
+                "",
+                velocity::gl330::TESS_CONTROL,
+                velocity::gl330::TESS_EVAL,
+            ]))
+        } else {
+            None
+        };
+        let velocity_direct = compile(&[velocity::gl330::VERT, velocity::gl330::FRAG]);
+        let sky = compile(&[skybox::gl330::VERT, sky::gl330::FRAG]);
+        let skybox = compile(&[skybox::gl330::VERT, skybox::gl330::FRAG]);
 
-        let mut post_shaders = HashMap::new();
+        let mut shaders = Shaders {
+            shaders: HashMap::new(),
+            direct_shaders: HashMap::new(),
+            post_shaders: HashMap::new(),
+            textures: HashMap::new(),
+            data_textures: HashMap::new(),
+            tessellation,
+            geometry,
+            gles,
+            chunks,
+            errors: HashMap::new(),
+            reload_interval: 0.5f64,
+            last_reload: time::precise_time_s(),
+            velocity,
+            velocity_direct,
+            sky,
+            skybox,
+            files: Vec::new(),
+        };
 
-        post_shaders.insert(
-            "default",
-            program!(display,
-                     330 => {
-                         vertex: post::gl330::VERT,
-                         fragment: post::gl330::FRAG,
-                     }).unwrap(),
-        );
+        // the shader programs used to render the RenderItems, with the fragment
+        // and geometry stages of each
+        let scene_shaders = [
+            ("dist", dist::gl330::FRAG, default::gl330::GEOM),
+            ("height", height::gl330::FRAG, default::gl330::GEOM),
+            ("line", line::gl330::FRAG, line::gl330::GEOM),
+            ("texture", texture::gl330::FRAG, default::gl330::GEOM),
+            ("reflect", reflect::gl330::FRAG, default::gl330::GEOM),
+            ("missing", missing::gl330::FRAG, default::gl330::GEOM),
+        ];
+        for &(name, frag, geom) in scene_shaders.iter() {
+            let stages = [
+                default::gl330::VERT,
+                frag,
+                geom,
+                default::gl330::TESS_CONTROL,
+                default::gl330::TESS_EVAL,
+            ];
+            let (target, program) = shaders
+                .compile_stages(display, ShaderTarget::Scene, &stages)
+                .unwrap();
+            shaders.insert_program(target, name, program);
+
+            // lines and points are always drawn direct
+            if target != ShaderTarget::Direct {
+                let program = shaders
+                    .compile_stages(
+                        display,
+                        ShaderTarget::Direct,
+                        &[default::gl330::DIRECT_VERT, frag],
+                    )
+                    .unwrap();
+                shaders.insert_program(ShaderTarget::Direct, name, program);
+            }
+        }
 
-        let mut textures = HashMap::new();
+        let post_shaders = [
+            ("default", post::gl330::FRAG),
+            ("copy", copy::gl330::FRAG),
+            ("adapt", adapt::gl330::FRAG),
+            ("tonemap", tonemap::gl330::FRAG),
+            ("bloom_bright", bloom::gl330::BRIGHT_FRAG),
+            ("bloom_downsample", bloom::gl330::DOWNSAMPLE_FRAG),
+            ("bloom_upsample", bloom::gl330::UPSAMPLE_FRAG),
+            ("bloom", bloom::gl330::FRAG),
+            ("ssao", ssao::gl330::FRAG),
+            ("ssao_blur", ssao::gl330::BLUR_FRAG),
+            ("fxaa", fxaa::gl330::FRAG),
+            ("taa", taa::gl330::FRAG),
+            ("grading", grading::gl330::FRAG),
+            ("motion_blur", motionblur::gl330::FRAG),
+            ("transition", transition::gl330::FRAG),
+            ("color_blind", colorblind::gl330::FRAG),
+        ];
+        for &(name, frag) in post_shaders.iter() {
+            let (target, program) = shaders
+                .compile_stages(display, ShaderTarget::Post, &[post::gl330::VERT, frag])
+                .unwrap();
+            shaders.insert_program(target, name, program);
+        }
 
-        textures.insert("default", load_texture!("../resources/caper.png", display));
-        textures.insert(
+        shaders.add_texture("default", load_texture!("../resources/caper.png", display));
+        shaders.add_texture(
             "default_normal",
             load_texture!("../resources/normal.png", display),
         );
+        shaders.add_texture("missing", checkerboard_texture(display));
+        shaders.add_data_texture(
+            "flat",
+            Texture2d::new(display, vec![vec![(0u8, 0u8, 0u8, 255u8)]]).unwrap(),
+        );
 
-        Shaders {
-            shaders,
-            post_shaders,
-            textures,
-        }
+        shaders
     }
 
-    /// Add a new shader to the map that can used for rendering the RenderItems
+    /// Add a new shader to the map that can used for rendering the RenderItems, without
+    /// tessellation the vertex and tessellation stages are replaced by the direct vertex shader
+    /// feeding the geometry stage, or by a direct shader when geometry shaders are unsupported
     pub fn add_shader(
         &mut self,
         display: &Display,
-        name: &'static str,
-        vert: &'static str,
-        frag: &'static str,
-        geom: &'static str,
-        tess_cont: &'static str,
-        tess_eval: &'static str,
-    ) -> Result<&str, &str> {
-        let shader_prog = match program!(display,
-                                         330 => {
-                                             vertex: vert,
-                                             fragment: frag,
-                                             geometry: geom,
-                                             tessellation_control: tess_cont,
-                                             tessellation_evaluation: tess_eval,
-                                         }) {
-            Ok(s) => s,
-            Err(e) => {
-                println!("{}", e.cause().unwrap());
-                return Err("Could not create shader");
-            }
-        };
-
-        self.shaders.insert(name, shader_prog);
+        name: &str,
+        vert: &str,
+        frag: &str,
+        geom: &str,
+        tess_cont: &str,
+        tess_eval: &str,
+    ) -> Result<(), String> {
+        let stages = [vert, frag, geom, tess_cont, tess_eval];
+        let (target, shader_prog) = self.compile_stages(display, ShaderTarget::Scene, &stages)?;
+        self.insert_program(target, name, shader_prog);
+        Ok(())
+    }
 
-        Ok("shader added")
+    /// Add a new shader to the direct_shaders map that can be used for rendering RenderItems
+    /// drawn as lines or points
+    pub fn add_direct_shader(
+        &mut self,
+        display: &Display,
+        name: &str,
+        vert: &str,
+        frag: &str,
+    ) -> Result<(), String> {
+        let (_, shader_prog) = self.compile_stages(display, ShaderTarget::Direct, &[vert, frag])?;
+        self.insert_program(ShaderTarget::Direct, name, shader_prog);
+        Ok(())
     }
 
     /// Add a new shader to the post_shaders map that can be used for rendering post processing
     pub fn add_post_shader(
         &mut self,
         display: &Display,
-        name: &'static str,
-        vert: &'static str,
-        frag: &'static str,
-    ) -> Result<&str, &str> {
-        let post_shader_prog = match program!(display,
-                                              330 => {
-                                                  vertex: vert,
-                                                  fragment: frag
-                                              }) {
-            Ok(s) => s,
+        name: &str,
+        vert: &str,
+        frag: &str,
+    ) -> Result<(), String> {
+        let (_, post_shader_prog) =
+            self.compile_stages(display, ShaderTarget::Post, &[vert, frag])?;
+        self.insert_program(ShaderTarget::Post, name, post_shader_prog);
+        Ok(())
+    }
+
+    /// The program the velocity of a topology is drawn with, matching how the material's
+    /// shader draws it
+    pub fn velocity_program(&self, topology: Topology) -> &Program {
+        match (topology, self.velocity.as_ref()) {
+            (Topology::TrianglesList, Some(velocity)) => velocity,
+            _ => &self.velocity_direct,
+        }
+    }
+
+    /// The program the procedural sky is drawn with
+    pub fn sky_program(&self) -> &Program {
+        &self.sky
+    }
+
+    /// The program the skybox is drawn with
+    pub fn skybox_program(&self) -> &Program {
+        &self.skybox
+    }
+
+    /// Add a texture that can be passed to the shaders
+    pub fn add_texture(&mut self, name: &str, texture: CompressedSrgbTexture2d) {
+        self.textures.insert(name.to_string(), texture);
+    }
+
+    /// Add a texture of linear data, such as a displacement map, that can be passed to the shaders
+    pub fn add_data_texture(&mut self, name: &str, texture: Texture2d) {
+        self.data_textures.insert(name.to_string(), texture);
+    }
+
+    /// Remove a shader used for rendering the RenderItems, returning it if found
+    pub fn remove_shader(&mut self, name: &str) -> Option<Program> {
+        self.stop_watching(name, ShaderTarget::Scene);
+        if self.tessellation || self.geometry {
+            self.shaders.remove(name)
+        } else {
+            self.direct_shaders.remove(name)
+        }
+    }
+
+    /// Remove a shader used for rendering RenderItems without tessellation, returning it if found
+    pub fn remove_direct_shader(&mut self, name: &str) -> Option<Program> {
+        self.stop_watching(name, ShaderTarget::Direct);
+        self.direct_shaders.remove(name)
+    }
+
+    /// Remove a post processing shader, returning it if found
+    pub fn remove_post_shader(&mut self, name: &str) -> Option<Program> {
+        self.stop_watching(name, ShaderTarget::Post);
+        self.post_shaders.remove(name)
+    }
+
+    /// Remove a texture, returning it if found
+    pub fn remove_texture(&mut self, name: &str) -> Option<CompressedSrgbTexture2d> {
+        self.textures.remove(name)
+    }
+
+    /// Remove a texture of linear data, returning it if found
+    pub fn remove_data_texture(&mut self, name: &str) -> Option<Texture2d> {
+        self.data_textures.remove(name)
+    }
+
+    /// Remove a chunk of GLSL, returning its source if found
+    pub fn remove_chunk(&mut self, name: &str) -> Option<String> {
+        self.chunks.remove(name)
+    }
+
+    /// The names of the shaders used for rendering the RenderItems
+    pub fn shader_names(&self) -> Vec<&str> {
+        self.shaders.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// The names of the shaders used for rendering RenderItems without tessellation
+    pub fn direct_shader_names(&self) -> Vec<&str> {
+        self.direct_shaders.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// The names of the post processing shaders
+    pub fn post_shader_names(&self) -> Vec<&str> {
+        self.post_shaders.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// The names of the textures that can be passed to the shaders
+    pub fn texture_names(&self) -> Vec<&str> {
+        self.textures.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// The names of the textures of linear data that can be passed to the shaders
+    pub fn data_texture_names(&self) -> Vec<&str> {
+        self.data_textures.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// The names of the chunks of GLSL that shaders can include
+    pub fn chunk_names(&self) -> Vec<&str> {
+        self.chunks.keys().map(|k| k.as_str()).collect()
+    }
+
+    /// The program a shader is drawn with for a topology, triangles come from the shaders map
+    /// when the context has tessellation or geometry shaders and everything else from
+    /// direct_shaders, a shader that is only in the other map is not used
+    pub fn program(&self, name: &str, topology: Topology) -> Option<&Program> {
+        match topology {
+            Topology::TrianglesList if self.tessellation || self.geometry => self.shaders.get(name),
+            _ => self.direct_shaders.get(name),
+        }
+    }
+
+    /// Checks that the shader a material uses exists for the topology and that its textures exist
+    pub fn validate_material(&self, material: &Material, topology: Topology) -> Result<(), String> {
+        let shader_name = material.shader_name.as_str();
+        if self.program(shader_name, topology).is_none() {
+            return Err(format!("No {:?} shader named {}", topology, shader_name));
+        }
+
+        let texture_names = [&material.texture_name, &material.normal_texture_name];
+        for name in texture_names.iter().filter_map(|n| n.as_ref()) {
+            if !self.textures.contains_key(name.as_str()) {
+                return Err(format!("No texture named {}", name));
+            }
+        }
+
+        if let Some(ref name) = material.tessellation.displacement_texture_name {
+            if !self.data_textures.contains_key(name.as_str()) {
+                return Err(format!("No data texture named {}", name));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add a named chunk of GLSL that shaders can #include
+    pub fn add_chunk(&mut self, name: &str, source: &str) {
+        self.chunks.insert(name.to_string(), source.to_string());
+    }
+
+    /// Resolves the #include lines of a shader source against the chunks and injects the
+    /// defines, for building variants of a shader
+    pub fn preprocess(&self, source: &str, defines: &[(&str, &str)]) -> Result<String, String> {
+        preprocess(&self.chunks, source, defines)
+    }
+
+    /// Preprocesses each stage of a shader without any defines
+    fn preprocess_stages(&self, sources: &[&str]) -> Result<Vec<String>, String> {
+        sources.iter().map(|s| self.preprocess(s, &[])).collect()
+    }
+
+    /// Load a shader from files that can be used for rendering the RenderItems,
+    /// it is recompiled whenever the files change
+    pub fn load_shader<P: AsRef<Path>>(
+        &mut self,
+        display: &Display,
+        name: &str,
+        vert: P,
+        frag: P,
+        geom: P,
+        tess_cont: P,
+        tess_eval: P,
+    ) -> Result<(), String> {
+        let paths = [vert, frag, geom, tess_cont, tess_eval];
+        self.watch_files(display, name, ShaderTarget::Scene, &paths)
+    }
+
+    /// Load a shader from files that can be used for rendering RenderItems without
+    /// tessellation, it is recompiled whenever the files change
+    pub fn load_direct_shader<P: AsRef<Path>>(
+        &mut self,
+        display: &Display,
+        name: &str,
+        vert: P,
+        frag: P,
+    ) -> Result<(), String> {
+        self.watch_files(display, name, ShaderTarget::Direct, &[vert, frag])
+    }
+
+    /// Load a shader from files that can be used for post processing,
+    /// it is recompiled whenever the files change
+    pub fn load_post_shader<P: AsRef<Path>>(
+        &mut self,
+        display: &Display,
+        name: &str,
+        vert: P,
+        frag: P,
+    ) -> Result<(), String> {
+        self.watch_files(display, name, ShaderTarget::Post, &[vert, frag])
+    }
+
+    /// Recompiles the shaders loaded from files that have changed since they were last compiled,
+    /// a shader that fails keeps its previous program and its error is kept in errors.
+    /// The files are checked at most once every reload_interval seconds
+    pub fn reload(&mut self, display: &Display) {
+        let now = time::precise_time_s();
+        if now - self.last_reload < self.reload_interval {
+            return;
+        }
+        self.last_reload = now;
+
+        for index in 0..self.files.len() {
+            let changed = {
+                let files = &self.files[index];
+                files
+                    .paths
+                    .iter()
+                    .zip(files.modified.iter())
+                    .any(|(path, modified)| modified_time(path) != *modified)
+            };
+            if changed {
+                let _ = self.compile_files(display, index);
+            }
+        }
+    }
+
+    /// Stops watching the files of a removed shader and forgets its error
+    fn stop_watching(&mut self, name: &str, target: ShaderTarget) {
+        let watched = self
+            .files
+            .iter()
+            .any(|f| f.name == name && f.target == target);
+        if watched {
+            self.files.retain(|f| f.name != name || f.target != target);
+            self.errors.remove(&(name.to_string(), target));
+        }
+    }
+
+    /// Starts watching the files of a shader, compiling it straight away
+    fn watch_files<P: AsRef<Path>>(
+        &mut self,
+        display: &Display,
+        name: &str,
+        target: ShaderTarget,
+        paths: &[P],
+    ) -> Result<(), String> {
+        // a shader with the same name and target stops being watched
+        self.files.retain(|f| f.name != name || f.target != target);
+        self.files.push(ShaderFiles {
+            name: name.to_string(),
+            target,
+            paths: paths.iter().map(|p| p.as_ref().to_path_buf()).collect(),
+            modified: Vec::new(),
+        });
+
+        let index = self.files.len() - 1;
+        self.compile_files(display, index)
+    }
+
+    /// Compiles a watched shader, replacing its program on success
+    fn compile_files(&mut self, display: &Display, index: usize) -> Result<(), String> {
+        let (name, target, paths) = {
+            let files = &mut self.files[index];
+            files.modified = files.paths.iter().map(|p| modified_time(p)).collect();
+            (files.name.clone(), files.target, files.paths.clone())
+        };
+
+        let result = paths
+            .iter()
+            .map(|p| read_source(p))
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|sources| self.compile_stages(display, target, &sources[..]));
+
+        match result {
+            Ok((map_target, program)) => {
+                self.errors.remove(&(name.clone(), target));
+                self.insert_program(map_target, &name, program);
+                Ok(())
+            }
             Err(e) => {
-                println!("{}", e.cause().unwrap());
-                return Err("Could not create post shader");
+                self.errors.insert((name, target), e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Preprocesses and compiles the stages of a shader for the context, returning the map the
+    /// program belongs in. Scene shaders without tessellation swap the vertex and tessellation
+    /// stages for the direct vertex shader, keeping the geometry stage when it is supported and
+    /// otherwise becoming a direct shader
+    fn compile_stages<S: AsRef<str>>(
+        &self,
+        display: &Display,
+        target: ShaderTarget,
+        stages: &[S],
+    ) -> Result<(ShaderTarget, Program), String> {
+        let stages = stages.iter().map(|s| s.as_ref()).collect::<Vec<_>>();
+        if stages.len() < 2 {
+            return Err("A shader needs at least a vertex and fragment stage".into());
+        }
+
+        let (target, sources) = match target {
+            ShaderTarget::Scene if self.tessellation => (target, self.preprocess_stages(&stages)?),
+            ShaderTarget::Scene if self.geometry && stages.len() > 2 => {
+                let vert = self.preprocess(default::gl330::DIRECT_VERT, &[("GEOMETRY", "1")])?;
+                let rest = self.preprocess_stages(&stages[1..3])?;
+                (target, vec![vert, rest[0].clone(), rest[1].clone()])
             }
+            ShaderTarget::Scene => {
+                let vert = self.preprocess(default::gl330::DIRECT_VERT, &[])?;
+                let frag = self.preprocess(stages[1], &[])?;
+                (ShaderTarget::Direct, vec![vert, frag])
+            }
+            _ => (target, self.preprocess_stages(&stages)?),
         };
 
-        self.post_shaders.insert(name, post_shader_prog);
+        compile_sources(display, self.gles, &sources)
+            .map(|program| (target, program))
+            .map_err(|e| annotate_error(e, &sources))
+    }
 
-        Ok("post shader added")
+    /// Puts a program in the map for its target, replacing any with the same name
+    fn insert_program(&mut self, target: ShaderTarget, name: &str, program: Program) {
+        let map = match target {
+            ShaderTarget::Scene => &mut self.shaders,
+            ShaderTarget::Direct => &mut self.direct_shaders,
+            ShaderTarget::Post => &mut self.post_shaders,
+        };
+        map.insert(name.to_string(), program);
+    }
+}
+
+/// Creates the magenta and black checkerboard drawn in place of missing textures
+fn checkerboard_texture(display: &Display) -> CompressedSrgbTexture2d {
+    let size = 64u32;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let check = ((x / 8) + (y / 8)) % 2 == 0;
+            let red_blue = if check { 255u8 } else { 0u8 };
+            data.extend_from_slice(&[red_blue, 0u8, red_blue, 255u8]);
+        }
+    }
+
+    CompressedSrgbTexture2d::new(display, RawImage2d::from_raw_rgba(data, (size, size))).unwrap()
+}
+
+/// Returns when the file was last modified, None if it can not be read
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Reads the source of a shader stage
+fn read_source(path: &Path) -> Result<String, String> {
+    let mut source = String::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut source))
+        .map_err(|e| format!("Could not load shader {}: {}", path.display(), e))?;
+    Ok(source)
+}
+
+/// Compiles the vertex and fragment stages, then geometry and tessellation if there are any
+/// and they are not empty, translating them to GLSL ES first for a GLES context
+fn compile_sources(display: &Display, gles: bool, sources: &[String]) -> Result<Program, String> {
+    let sources = if gles {
+        sources.iter().map(|s| gles_source(s)).collect()
+    } else {
+        sources.to_vec()
+    };
+    let stage = |i: usize| {
+        sources
+            .get(i)
+            .map(|s| s.as_str())
+            .filter(|s| !s.trim().is_empty())
+    };
+    Program::new(
+        display,
+        SourceCode {
+            vertex_shader: &sources[0],
+            fragment_shader: &sources[1],
+            geometry_shader: stage(2),
+            tessellation_control_shader: stage(3),
+            tessellation_evaluation_shader: stage(4),
+        },
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Swaps the #version line of a GLSL 3.30 source for GLSL ES 3.00 and the default precisions
+/// it needs
+pub fn gles_source(source: &str) -> String {
+    let mut output = String::with_capacity(source.len() + GLES_HEADER.len());
+    let mut replaced = false;
+    for (i, line) in source.lines().enumerate() {
+        if !replaced && line.trim().starts_with("#version") {
+            // keep the line numbers of the rest of the source
+            output.push_str(GLES_HEADER);
+            output.push_str(&format!("#line {} 0\n", i + 2));
+            replaced = true;
+        } else {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// The version and precisions a GLSL ES shader starts with
+const GLES_HEADER: &str = "#version 300 es
+precision highp float;
+precision highp int;
+precision highp sampler2D;
+precision highp sampler3D;
+precision highp samplerCube;
+";
+
+/// Resolves `#include "name"` lines against the chunks, including each chunk once, and injects
+/// a `#define name value` for each define after the #version line. Each included chunk is
+/// numbered as a GLSL source string from 1 with `#line` directives, so compile errors point at
+/// the line in the chunk, and a `// source n is chunk "name"` comment is added for each
+pub fn preprocess(
+    chunks: &HashMap<String, String>,
+    source: &str,
+    defines: &[(&str, &str)],
+) -> Result<String, String> {
+    let mut included = Vec::new();
+    let body = resolve_includes(chunks, source, 0, &mut included)?;
+    let mut header = defines
+        .iter()
+        .map(|&(name, value)| format!("#define {} {}\n", name, value))
+        .collect::<String>();
+    for (i, name) in included.iter().enumerate() {
+        let legend = format!("{}{} is chunk \"{}\"\n", SOURCE_LEGEND, i + 1, name);
+        header.push_str(&legend);
+    }
+
+    // the version has to stay the first statement
+    let version_line = body.lines().position(|l| l.trim().starts_with("#version"));
+    if !header.is_empty() {
+        let next_line = version_line.map_or(1, |i| i + 2);
+        header.push_str(&format!("#line {} 0\n", next_line));
+    }
+    let mut output = String::with_capacity(body.len() + header.len());
+    if version_line.is_none() {
+        output.push_str(&header);
+    }
+    for (i, line) in body.lines().enumerate() {
+        output.push_str(line);
+        output.push('\n');
+        if Some(i) == version_line {
+            output.push_str(&header);
+        }
+    }
+
+    Ok(output)
+}
+
+/// The start of the comments naming the chunk each source string number is
+const SOURCE_LEGEND: &str = "// source ";
+
+/// Replaces the #include lines of a source with their chunks, skipping chunks already included,
+/// and numbers each chunk as a source string for its line numbers
+fn resolve_includes(
+    chunks: &HashMap<String, String>,
+    source: &str,
+    source_number: usize,
+    included: &mut Vec<String>,
+) -> Result<String, String> {
+    let mut output = String::with_capacity(source.len());
+    for (i, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+        if !trimmed.starts_with("#include") {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        }
+
+        let name = trimmed["#include".len()..].trim();
+        if name.len() < 2 || !name.starts_with('"') || !name.ends_with('"') {
+            return Err(format!("Invalid shader include: {}", trimmed));
+        }
+        let name = &name[1..name.len() - 1];
+        if included.iter().any(|n| n == name) {
+            // an empty line keeps the line numbers after it
+            output.push('\n');
+            continue;
+        }
+
+        let chunk = chunks
+            .get(name)
+            .ok_or_else(|| format!("No shader chunk named {}", name))?;
+        included.push(name.to_string());
+        let chunk_number = included.len();
+        output.push_str(&format!("#line 1 {}\n", chunk_number));
+        output.push_str(&resolve_includes(chunks, chunk, chunk_number, included)?);
+        output.push_str(&format!("#line {} {}\n", i + 2, source_number));
+    }
+
+    Ok(output)
+}
+
+/// Adds which chunk each source string number of each stage is to a compile error
+fn annotate_error(error: String, sources: &[String]) -> String {
+    let stages = [
+        "vertex",
+        "fragment",
+        "geometry",
+        "tessellation control",
+        "tessellation evaluation",
+    ];
+    let mut output = error;
+    for (stage, source) in stages.iter().zip(sources.iter()) {
+        let legend = source
+            .lines()
+            .filter(|l| l.starts_with(SOURCE_LEGEND))
+            .map(|l| &l[3..])
+            .collect::<Vec<_>>();
+        if !legend.is_empty() {
+            output.push_str(&format!("\nIn the {} stage {}", stage, legend.join(", ")));
+        }
     }
+    output
 }
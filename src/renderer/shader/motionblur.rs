@@ -0,0 +1,62 @@
+/// OpenGL 3.3 shaders for motion blur
+pub mod gl330 {
+    /// Motion blur fragment shader, blurs along the per-pixel velocity
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+        uniform sampler2D velocity_tex;
+        uniform sampler2D depth_buf;
+
+        uniform bool has_velocity;
+        uniform mat4 projection_matrix;
+        uniform mat4 modelview_matrix;
+        uniform mat4 prev_projection_matrix;
+        uniform mat4 prev_modelview_matrix;
+
+        uniform float motion_blur_shutter_angle;
+        uniform int motion_blur_samples;
+        uniform float motion_blur_max_length;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        const int max_samples = 32;
+
+        void main() {
+            vec4 velocity_sample = texture(velocity_tex, v_tex_coords);
+            vec2 velocity;
+            if (has_velocity && velocity_sample.a > 0.0) {
+                velocity = velocity_sample.xy;
+            } else {
+                // nothing drawn here, so use the camera movement from the depth
+                float depth = texture(depth_buf, v_tex_coords).r;
+                mat4 inv_view_proj = inverse(projection_matrix * modelview_matrix);
+                vec4 world_pos = inv_view_proj * vec4(vec3(v_tex_coords, depth) * 2.0 - 1.0, 1.0);
+                world_pos /= world_pos.w;
+                vec4 prev_pos = prev_projection_matrix * prev_modelview_matrix * world_pos;
+                velocity = v_tex_coords - (prev_pos.xy / prev_pos.w * 0.5 + 0.5);
+            }
+
+            // the shutter is only open for part of the frame
+            velocity *= motion_blur_shutter_angle / 360.0;
+            float len = length(velocity);
+            if (len > motion_blur_max_length) {
+                velocity *= motion_blur_max_length / len;
+            }
+
+            int samples = clamp(motion_blur_samples, 2, max_samples);
+            vec4 color = vec4(0.0);
+            for (int i = 0; i < max_samples; i++) {
+                if (i >= samples) {
+                    break;
+                }
+                float t = float(i) / float(samples - 1) - 0.5;
+                color += texture(tex, v_tex_coords - velocity * t);
+            }
+
+            frag_output = color / float(samples);
+        }
+    ";
+}
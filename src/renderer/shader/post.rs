@@ -53,19 +53,6 @@ pub mod gl330 {
 
         out vec4 frag_output;
 
-        float w_offset[4] = float[](
-            (-1.5 * blur_radius) / resolution.x,
-            (-0.5 * blur_radius) / resolution.x,
-            (0.5 * blur_radius) / resolution.x,
-            (1.5 * blur_radius) / resolution.x
-        );
-        float h_offset[4] = float[](
-            (-1.5 * blur_radius) / resolution.y,
-            (-0.5 * blur_radius) / resolution.y,
-            (0.5 * blur_radius) / resolution.y,
-            (1.5 * blur_radius) / resolution.y
-        );
-
         float rand (vec2 s) {
             return fract(sin(dot(s, vec2(12.9898, 78.233))) * 43758.5453);
         }
@@ -86,6 +73,20 @@ pub mod gl330 {
             // blur
             vec3 blur_color = color.rgb;
             if (blur || bokeh) {
+                // offsets are set in main as GLSL ES has no non-constant global initializers
+                float w_offset[4] = float[](
+                    (-1.5 * blur_radius) / resolution.x,
+                    (-0.5 * blur_radius) / resolution.x,
+                    (0.5 * blur_radius) / resolution.x,
+                    (1.5 * blur_radius) / resolution.x
+                );
+                float h_offset[4] = float[](
+                    (-1.5 * blur_radius) / resolution.y,
+                    (-0.5 * blur_radius) / resolution.y,
+                    (0.5 * blur_radius) / resolution.y,
+                    (1.5 * blur_radius) / resolution.y
+                );
+
                for (int i = 0 ; i < 4 ; i++) {
                     for (int j = 0 ; j < 4 ; j++) {
                         vec2 sample_pos =
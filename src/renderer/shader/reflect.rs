@@ -0,0 +1,36 @@
+/// OpenGL 3.3 shaders for reflecting the environment map
+pub mod gl330 {
+    /// Reflective fragment shader that mixes in the skybox by the fresnel term
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform vec3 cam_pos;
+        uniform sampler2D dir_lights;
+        uniform samplerCube env_map;
+        uniform bool has_env_map;
+
+        #include \"lighting\"
+        #include \"scene_fog\"
+
+        in vec3 g_normal;
+        in vec3 g_pos;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec3 normal = normalize(g_normal);
+            vec3 view = normalize(g_pos - cam_pos);
+
+            float lum = dir_light_lum(dir_lights, normal);
+            vec3 color = vec3(0.3 + (0.4 * lum));
+
+            if (has_env_map) {
+                vec3 reflected = texture(env_map, reflect(view, normal)).rgb;
+                float fresnel = 0.04 + 0.96 * pow(1.0 - max(dot(-view, normal), 0.0), 5.0);
+                color = mix(color * 0.5, reflected, clamp(0.5 + fresnel, 0.0, 1.0));
+            }
+
+            frag_output = vec4(apply_scene_fog(color, cam_pos, g_pos), 1.0);
+        }
+    ";
+}
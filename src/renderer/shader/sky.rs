@@ -0,0 +1,89 @@
+/// OpenGL 3.3 shaders for the procedural sky
+pub mod gl330 {
+    /// Sky fragment shader using the Preetham daylight model, drawn with the skybox vertex shader
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform vec3 sun_dir;
+        uniform vec3 sun_color;
+        uniform vec3 fog_color;
+        uniform float sky_turbidity;
+        uniform float sky_intensity;
+
+        in vec3 v_dir;
+
+        out vec4 frag_output;
+
+        // the Perez sky luminance distribution
+        float perez(float theta, float gamma, float a, float b, float c, float d, float e) {
+            float cos_gamma = cos(gamma);
+            return (1.0 + a * exp(b / max(cos(theta), 0.01)))
+                * (1.0 + c * exp(d * gamma) + e * cos_gamma * cos_gamma);
+        }
+
+        void main() {
+            float t = sky_turbidity;
+            vec3 dir = normalize(v_dir);
+            vec3 sun = normalize(sun_dir);
+
+            // below the horizon the sky is kept at the horizon and the sun at its lowest
+            float theta = acos(clamp(dir.y, 0.001, 1.0));
+            float theta_s = acos(clamp(sun.y, 0.01, 1.0));
+            float gamma = acos(clamp(dot(normalize(vec3(dir.x, max(dir.y, 0.001), dir.z)), sun),
+                                     -1.0, 1.0));
+
+            // distribution coefficients for luminance and chromaticity
+            float a_y = 0.1787 * t - 1.4630;
+            float b_y = -0.3554 * t + 0.4275;
+            float c_y = -0.0227 * t + 5.3251;
+            float d_y = 0.1206 * t - 2.5771;
+            float e_y = -0.0670 * t + 0.3703;
+
+            float a_cx = -0.0193 * t - 0.2592;
+            float b_cx = -0.0665 * t + 0.0008;
+            float c_cx = -0.0004 * t + 0.2125;
+            float d_cx = -0.0641 * t - 0.8989;
+            float e_cx = -0.0033 * t + 0.0452;
+
+            float a_cy = -0.0167 * t - 0.2608;
+            float b_cy = -0.0950 * t + 0.0092;
+            float c_cy = -0.0079 * t + 0.2102;
+            float d_cy = -0.0441 * t - 1.6537;
+            float e_cy = -0.0109 * t + 0.0529;
+
+            // the sky at the zenith
+            float chi = (4.0 / 9.0 - t / 120.0) * (3.14159265 - 2.0 * theta_s);
+            float zenith_y = (4.0453 * t - 4.9710) * tan(chi) - 0.2155 * t + 2.4192;
+            vec3 ts = vec3(theta_s * theta_s * theta_s, theta_s * theta_s, theta_s);
+            float zenith_cx = t * t * dot(vec3(0.00166, -0.00375, 0.00209), ts)
+                + t * (dot(vec3(-0.02903, 0.06377, -0.03202), ts) + 0.00394)
+                + dot(vec3(0.11693, -0.21196, 0.06052), ts) + 0.25886;
+            float zenith_cy = t * t * dot(vec3(0.00275, -0.00610, 0.00317), ts)
+                + t * (dot(vec3(-0.04214, 0.08970, -0.04153), ts) + 0.00516)
+                + dot(vec3(0.15346, -0.26756, 0.06670), ts) + 0.26688;
+
+            float lum = zenith_y * perez(theta, gamma, a_y, b_y, c_y, d_y, e_y)
+                / perez(0.0, theta_s, a_y, b_y, c_y, d_y, e_y);
+            float cx = zenith_cx * perez(theta, gamma, a_cx, b_cx, c_cx, d_cx, e_cx)
+                / perez(0.0, theta_s, a_cx, b_cx, c_cx, d_cx, e_cx);
+            float cy = zenith_cy * perez(theta, gamma, a_cy, b_cy, c_cy, d_cy, e_cy)
+                / perez(0.0, theta_s, a_cy, b_cy, c_cy, d_cy, e_cy);
+
+            // xyY to linear rgb, with luminance in kcd/m2 brought down to around 1
+            vec3 xyz = vec3(cx / cy * lum, lum, (1.0 - cx - cy) / cy * lum) * 0.05;
+            vec3 color = mat3(3.2406, -0.9689, 0.0557,
+                              -1.5372, 1.8758, -0.2040,
+                              -0.4986, 0.0415, 1.0570) * xyz;
+
+            // fades to the night sky as the sun sets
+            float daylight = smoothstep(-0.1, 0.1, sun.y);
+            color = mix(fog_color * 0.5, max(color, vec3(0.0)), daylight);
+
+            // the sun disc and the ground below the horizon
+            color += sun_color * smoothstep(0.9995, 0.9998, dot(dir, sun)) * 20.0;
+            color = mix(color, fog_color * 0.5, smoothstep(0.0, -0.1, dir.y));
+
+            frag_output = vec4(color * sky_intensity, 1.0);
+        }
+    ";
+}
@@ -0,0 +1,37 @@
+/// OpenGL 3.3 shaders for drawing the skybox
+pub mod gl330 {
+    /// Skybox vertex shader, drawn over the full screen quad at the far plane
+    pub const VERT: &str = "
+        #version 330
+
+        uniform mat4 projection_matrix;
+        uniform mat4 modelview_matrix;
+
+        layout(location = 0) in vec3 position;
+        layout(location = 1) in vec2 texture;
+
+        out vec3 v_dir;
+
+        void main() {
+            // the view direction through this corner of the screen in world space
+            vec4 view = inverse(projection_matrix) * vec4(position.xy, 1.0, 1.0);
+            v_dir = inverse(mat3(modelview_matrix)) * (view.xyz / view.w);
+            gl_Position = vec4(position.xy, 1.0, 1.0);
+        }
+    ";
+    /// Skybox fragment shader sampling the environment cubemap
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform samplerCube env_map;
+        uniform float skybox_intensity;
+
+        in vec3 v_dir;
+
+        out vec4 frag_output;
+
+        void main() {
+            frag_output = vec4(texture(env_map, normalize(v_dir)).rgb * skybox_intensity, 1.0);
+        }
+    ";
+}
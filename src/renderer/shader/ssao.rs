@@ -0,0 +1,127 @@
+/// OpenGL 3.3 shaders for screen space ambient occlusion
+pub mod gl330 {
+    /// Ambient occlusion fragment shader, outputs the occlusion from the scene depth
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D depth_buf;
+        uniform mat4 projection_matrix;
+
+        uniform float ssao_radius;
+        uniform int ssao_samples;
+        uniform float ssao_strength;
+        uniform float ssao_bias;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        const int max_samples = 64;
+
+        float rand(vec2 co) {
+            return fract(sin(dot(co, vec2(12.9898, 78.233))) * 43758.5453);
+        }
+
+        // reconstruct the view space position from the depth buffer
+        vec3 view_pos(mat4 inv_proj, vec2 uv) {
+            float depth = texture(depth_buf, uv).r;
+            vec4 pos = inv_proj * vec4(vec3(uv, depth) * 2.0 - 1.0, 1.0);
+            return pos.xyz / pos.w;
+        }
+
+        void main() {
+            // nothing to occlude on the background
+            if (texture(depth_buf, v_tex_coords).r >= 1.0) {
+                frag_output = vec4(1.0);
+                return;
+            }
+
+            mat4 inv_proj = inverse(projection_matrix);
+            vec3 pos = view_pos(inv_proj, v_tex_coords);
+            vec3 normal = normalize(cross(dFdx(pos), dFdy(pos)));
+
+            // randomly rotated basis around the normal to break up banding
+            vec3 random = normalize(vec3(
+                rand(v_tex_coords) * 2.0 - 1.0,
+                rand(v_tex_coords + 1.3) * 2.0 - 1.0,
+                0.0));
+            vec3 tangent = normalize(random - normal * dot(random, normal));
+            vec3 bitangent = cross(normal, tangent);
+            mat3 tbn = mat3(tangent, bitangent, normal);
+
+            int samples = clamp(ssao_samples, 1, max_samples);
+            float occlusion = 0.0;
+            for (int i = 0; i < max_samples; i++) {
+                if (i >= samples) {
+                    break;
+                }
+
+                // point in the hemisphere, clustered towards the centre
+                vec3 dir = normalize(vec3(
+                    rand(vec2(i, 0.5)) * 2.0 - 1.0,
+                    rand(vec2(i, 1.5)) * 2.0 - 1.0,
+                    rand(vec2(i, 2.5))));
+                float scale = float(i) / float(samples);
+                scale = mix(0.1, 1.0, scale * scale);
+                vec3 sample_pos = pos + tbn * dir * scale * ssao_radius;
+
+                // project the point to find the depth in front of it
+                vec4 offset = projection_matrix * vec4(sample_pos, 1.0);
+                offset.xy = offset.xy / offset.w * 0.5 + 0.5;
+                float sample_depth = view_pos(inv_proj, offset.xy).z;
+
+                float range = smoothstep(0.0, 1.0, ssao_radius / abs(pos.z - sample_depth));
+                occlusion += (sample_depth >= sample_pos.z + ssao_bias ? 1.0 : 0.0) * range;
+            }
+
+            float ao = pow(1.0 - occlusion / float(samples), ssao_strength);
+
+            frag_output = vec4(ao);
+        }
+    ";
+
+    /// Bilateral blur fragment shader, smooths the occlusion without crossing depth edges
+    /// and darkens the scene with it
+    pub const BLUR_FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+        uniform sampler2D ao_tex;
+        uniform sampler2D depth_buf;
+        uniform mat4 projection_matrix;
+
+        uniform float ssao_blur_sharpness;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        float view_depth(mat4 inv_proj, vec2 uv) {
+            float depth = texture(depth_buf, uv).r;
+            vec4 pos = inv_proj * vec4(vec3(uv, depth) * 2.0 - 1.0, 1.0);
+            return pos.z / pos.w;
+        }
+
+        void main() {
+            mat4 inv_proj = inverse(projection_matrix);
+            vec2 texel = 1.0 / vec2(textureSize(ao_tex, 0));
+            float center_depth = view_depth(inv_proj, v_tex_coords);
+
+            float ao = 0.0;
+            float total_weight = 0.0;
+            for (int x = -2; x <= 2; x++) {
+                for (int y = -2; y <= 2; y++) {
+                    vec2 uv = v_tex_coords + vec2(x, y) * texel;
+                    float depth_diff = abs(view_depth(inv_proj, uv) - center_depth);
+                    float weight = exp(-depth_diff * ssao_blur_sharpness);
+                    ao += texture(ao_tex, uv).r * weight;
+                    total_weight += weight;
+                }
+            }
+            ao /= total_weight;
+
+            vec4 color = texture(tex, v_tex_coords);
+            frag_output = vec4(color.rgb * ao, color.a);
+        }
+    ";
+}
@@ -0,0 +1,59 @@
+/// OpenGL 3.3 shaders for temporal anti-aliasing
+pub mod gl330 {
+    /// TAA fragment shader, blends the jittered frame with the reprojected history
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+        uniform sampler2D depth_buf;
+        uniform sampler2D taa_history;
+
+        uniform bool has_history;
+        uniform mat4 projection_matrix;
+        uniform mat4 modelview_matrix;
+        uniform mat4 prev_projection_matrix;
+        uniform mat4 prev_modelview_matrix;
+
+        uniform float taa_feedback;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec4 current = texture(tex, v_tex_coords);
+            if (!has_history) {
+                frag_output = current;
+                return;
+            }
+
+            // reconstruct the world position and find where it was last frame
+            float depth = texture(depth_buf, v_tex_coords).r;
+            mat4 inv_view_proj = inverse(projection_matrix * modelview_matrix);
+            vec4 world_pos = inv_view_proj * vec4(vec3(v_tex_coords, depth) * 2.0 - 1.0, 1.0);
+            world_pos /= world_pos.w;
+            vec4 prev_pos = prev_projection_matrix * prev_modelview_matrix * world_pos;
+            vec2 prev_coords = prev_pos.xy / prev_pos.w * 0.5 + 0.5;
+
+            if (any(lessThan(prev_coords, vec2(0.0))) || any(greaterThan(prev_coords, vec2(1.0)))) {
+                frag_output = current;
+                return;
+            }
+
+            // clamp the history to the neighbourhood to stop ghosting
+            vec2 texel = 1.0 / vec2(textureSize(tex, 0));
+            vec3 min_color = current.rgb;
+            vec3 max_color = current.rgb;
+            for (int x = -1; x <= 1; x++) {
+                for (int y = -1; y <= 1; y++) {
+                    vec3 color = texture(tex, v_tex_coords + vec2(x, y) * texel).rgb;
+                    min_color = min(min_color, color);
+                    max_color = max(max_color, color);
+                }
+            }
+            vec3 history = clamp(texture(taa_history, prev_coords).rgb, min_color, max_color);
+
+            frag_output = vec4(mix(current.rgb, history, taa_feedback), current.a);
+        }
+    ";
+}
@@ -1,6 +1,6 @@
 /// OpenGL 3.3 shaders for distance roll off
 pub mod gl330 {
-    /// Distance fragment shader that rolls off to white the further from the camera
+    /// Texture fragment shader that fades into the fog the further from the camera
     pub const FRAG: &str = "
         #version 330
 
@@ -8,7 +8,9 @@ pub mod gl330 {
         uniform vec3 cam_pos;
         uniform sampler2D tex;
         uniform sampler2D normal_tex;
-        uniform sampler1D dir_lights;
+        uniform sampler2D dir_lights;
+
+        #include \"scene_fog\"
 
         in vec3 g_normal;
         in vec3 g_pos;
@@ -17,20 +19,19 @@ pub mod gl330 {
         out vec4 frag_output;
 
         void main() {
-            int size = textureSize(dir_lights, 0);
+            int size = textureSize(dir_lights, 0).x;
             float lum = 0.0;
             float tex_lum = 0.0;
             for (int i = 0; i < size; i++) {
-                vec3 light_norm = normalize(texture(dir_lights, i).xyz);
+                vec3 light_norm = normalize(texelFetch(dir_lights, ivec2(i, 0), 0).xyz);
                 lum += max(dot(normalize(g_normal), light_norm), 0.0);
                 tex_lum += dot(normalize(vec3(texture(normal_tex, g_texture))), light_norm);
             }
 
             float avg_lum = (lum + tex_lum) / 2.0;
 
-            float dist = abs(distance(cam_pos, g_pos)) / 80.0;
-
-            frag_output = texture(tex, g_texture) * vec4(vec3((0.6 * avg_lum) + (0.4 * dist)), 1.0);
+            vec4 color = texture(tex, g_texture) * vec4(vec3((0.6 * avg_lum) + 0.4), 1.0);
+            frag_output = vec4(apply_scene_fog(color.rgb, cam_pos, g_pos), color.a);
         }
     ";
 }
@@ -0,0 +1,70 @@
+/// OpenGL 3.3 shaders for tone mapping a HDR scene
+pub mod gl330 {
+    /// Tone mapping fragment shader, maps linear HDR colour to linear LDR
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+        uniform sampler2D adapt_tex;
+
+        uniform bool auto_exposure;
+        uniform float exposure;
+        uniform float exposure_key;
+        uniform int tonemap_operator;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        vec3 reinhard(vec3 color) {
+            return color / (color + vec3(1.0));
+        }
+
+        // Narkowicz fit of the ACES filmic curve
+        vec3 aces(vec3 color) {
+            const float a = 2.51;
+            const float b = 0.03;
+            const float c = 2.43;
+            const float d = 0.59;
+            const float e = 0.14;
+            return clamp((color * (a * color + b)) / (color * (c * color + d) + e), 0.0, 1.0);
+        }
+
+        // Hable's Uncharted 2 filmic curve
+        vec3 hable(vec3 x) {
+            const float A = 0.15;
+            const float B = 0.50;
+            const float C = 0.10;
+            const float D = 0.20;
+            const float E = 0.02;
+            const float F = 0.30;
+            return ((x * (A * x + C * B) + D * E) / (x * (A * x + B) + D * F)) - E / F;
+        }
+
+        vec3 filmic(vec3 color) {
+            const float W = 11.2;
+            const float exposure_bias = 2.0;
+            return hable(color * exposure_bias) / hable(vec3(W));
+        }
+
+        void main() {
+            vec3 color = texture(tex, v_tex_coords).rgb * exposure;
+
+            // scale so the average luminance maps to the key value
+            if (auto_exposure) {
+                float avg_lum = texture(adapt_tex, vec2(0.5)).r;
+                color *= exposure_key / max(avg_lum, 0.0001);
+            }
+
+            if (tonemap_operator == 0) {
+                color = reinhard(color);
+            } else if (tonemap_operator == 1) {
+                color = aces(color);
+            } else {
+                color = filmic(color);
+            }
+
+            frag_output = vec4(color, 1.0);
+        }
+    ";
+}
@@ -0,0 +1,42 @@
+/// OpenGL 3.3 shaders for screen transitions
+pub mod gl330 {
+    /// Transition fragment shader, covers the screen with a colour, the kept frame or a wipe
+    pub const FRAG: &str = "
+        #version 330
+
+        uniform sampler2D tex;
+        uniform sampler2D transition_history;
+        uniform sampler2D transition_mask;
+
+        uniform bool has_history;
+        uniform int transition_type;
+        uniform vec3 transition_color;
+        uniform float transition_amount;
+        uniform float transition_softness;
+
+        in vec2 v_tex_coords;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec4 color = texture(tex, v_tex_coords);
+
+            if (transition_type == 1) {
+                // fade to colour
+                color.rgb = mix(color.rgb, transition_color, transition_amount);
+            } else if (transition_type == 2 && has_history) {
+                // crossfade from the kept frame
+                vec3 last = texture(transition_history, v_tex_coords).rgb;
+                color.rgb = mix(color.rgb, last, transition_amount);
+            } else if (transition_type == 3) {
+                // wipe with darker parts of the mask covered first
+                float mask = texture(transition_mask, v_tex_coords).r;
+                float edge = transition_amount * (1.0 + transition_softness);
+                float covered = smoothstep(mask, mask + transition_softness, edge);
+                color.rgb = mix(color.rgb, vec3(0.0), covered);
+            }
+
+            frag_output = color;
+        }
+    ";
+}
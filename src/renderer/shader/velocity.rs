@@ -0,0 +1,172 @@
+/// OpenGL 3.3 shaders for rendering the screen space velocity of render items
+pub mod gl330 {
+    /// Velocity vertex shader for drawing without tessellation, transforms each vertex with
+    /// this and last frame's instances
+    pub const VERT: &str = "
+        #version 330
+
+        layout(location = 0) in vec3 position;
+        layout(location = 2) in vec3 world_position;
+        layout(location = 3) in vec4 world_rotation;
+        layout(location = 4) in vec3 world_scale;
+        in vec3 prev_world_position;
+        in vec4 prev_world_rotation;
+        in vec3 prev_world_scale;
+
+        uniform mat4 projection_matrix;
+        uniform mat4 modelview_matrix;
+        uniform mat4 base_projection_matrix;
+        uniform mat4 prev_projection_matrix;
+        uniform mat4 prev_modelview_matrix;
+
+        out vec4 v_curr;
+        out vec4 v_prev;
+
+        #include \"quaternion\"
+
+        void main() {
+            vec4 pos = vec4(
+                transform_instance(position, world_position, world_rotation, world_scale),
+                1.0);
+            vec4 prev_pos = vec4(
+                transform_instance(
+                    position, prev_world_position, prev_world_rotation, prev_world_scale),
+                1.0);
+
+            gl_Position = projection_matrix * modelview_matrix * pos;
+
+            // without jitter so it does not show up as movement
+            v_curr = base_projection_matrix * modelview_matrix * pos;
+            v_prev = prev_projection_matrix * prev_modelview_matrix * prev_pos;
+        }
+    ";
+
+    /// Velocity vertex shader for tessellated triangles, places each vertex with this and last
+    /// frame's instances
+    pub const TESS_VERT: &str = "
+        #version 330
+
+        layout(location = 0) in vec3 position;
+        layout(location = 1) in vec3 normal;
+        layout(location = 2) in vec3 world_position;
+        layout(location = 3) in vec4 world_rotation;
+        layout(location = 4) in vec3 world_scale;
+        layout(location = 5) in vec2 texture;
+        in vec3 prev_world_position;
+        in vec4 prev_world_rotation;
+        in vec3 prev_world_scale;
+
+        #include \"quaternion\"
+
+        out vec3 v_normal;
+        out vec2 v_texture;
+        out vec3 v_prev_pos;
+        out vec3 v_prev_normal;
+
+        void main() {
+            gl_Position = vec4(
+                transform_instance(position, world_position, world_rotation, world_scale),
+                1.0);
+            v_normal = transform_normal(normal, world_rotation, world_scale);
+            v_texture = texture;
+
+            v_prev_pos = transform_instance(
+                position, prev_world_position, prev_world_rotation, prev_world_scale);
+            v_prev_normal = transform_normal(normal, prev_world_rotation, prev_world_scale);
+        }
+    ";
+
+    /// Velocity tessellation control shader, subdivides like the default tessellation
+    pub const TESS_CONTROL: &str = "
+        #version 400
+
+        layout(vertices = 3) out;
+
+        #include \"tessellation_levels\"
+
+        in vec3 v_normal[];
+        in vec2 v_texture[];
+        in vec3 v_prev_pos[];
+        in vec3 v_prev_normal[];
+
+        out vec3 tc_normal[];
+        out vec2 tc_texture[];
+        out vec3 tc_prev_pos[];
+        out vec3 tc_prev_normal[];
+
+        void main() {
+            tc_normal[gl_InvocationID] = v_normal[gl_InvocationID];
+            tc_texture[gl_InvocationID] = v_texture[gl_InvocationID];
+            tc_prev_pos[gl_InvocationID] = v_prev_pos[gl_InvocationID];
+            tc_prev_normal[gl_InvocationID] = v_prev_normal[gl_InvocationID];
+            gl_out[gl_InvocationID].gl_Position = gl_in[gl_InvocationID].gl_Position;
+
+            if (gl_InvocationID == 0) {
+                set_tess_levels();
+            }
+        }
+    ";
+
+    /// Velocity tessellation evaluation shader, displaces both positions like the default
+    /// tessellation so the velocity lines up with the drawn surface
+    pub const TESS_EVAL: &str = "
+        #version 400
+
+        uniform mat4 projection_matrix;
+        uniform mat4 modelview_matrix;
+        uniform mat4 base_projection_matrix;
+        uniform mat4 prev_projection_matrix;
+        uniform mat4 prev_modelview_matrix;
+        uniform sampler2D displacement_tex;
+        uniform bool has_displacement;
+        uniform float displacement_scale;
+
+        layout(triangles, equal_spacing, ccw) in;
+
+        in vec3 tc_normal[];
+        in vec2 tc_texture[];
+        in vec3 tc_prev_pos[];
+        in vec3 tc_prev_normal[];
+
+        out vec4 v_curr;
+        out vec4 v_prev;
+
+        #include \"tessellation\"
+
+        void main() {
+            vec3 pos = tess_calc(gl_in[0].gl_Position.xyz,
+                gl_in[1].gl_Position.xyz,
+                gl_in[2].gl_Position.xyz);
+            vec3 prev_pos = tess_calc(tc_prev_pos[0], tc_prev_pos[1], tc_prev_pos[2]);
+
+            if (has_displacement) {
+                vec2 tex_coords = tex_calc(tc_texture[0], tc_texture[1], tc_texture[2]);
+                float height = texture(displacement_tex, tex_coords).r * displacement_scale;
+                pos += normalize(tess_calc(tc_normal[0], tc_normal[1], tc_normal[2])) * height;
+                prev_pos += normalize(
+                    tess_calc(tc_prev_normal[0], tc_prev_normal[1], tc_prev_normal[2])) * height;
+            }
+
+            gl_Position = projection_matrix * modelview_matrix * vec4(pos, 1.0);
+
+            // without jitter so it does not show up as movement
+            v_curr = base_projection_matrix * modelview_matrix * vec4(pos, 1.0);
+            v_prev = prev_projection_matrix * prev_modelview_matrix * vec4(prev_pos, 1.0);
+        }
+    ";
+
+    /// Velocity fragment shader, outputs the screen space movement since the last frame
+    pub const FRAG: &str = "
+        #version 330
+
+        in vec4 v_curr;
+        in vec4 v_prev;
+
+        out vec4 frag_output;
+
+        void main() {
+            vec2 velocity = (v_curr.xy / v_curr.w - v_prev.xy / v_prev.w) * 0.5;
+            frag_output = vec4(velocity, 0.0, 1.0);
+        }
+    ";
+}
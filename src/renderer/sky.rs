@@ -0,0 +1,116 @@
+use std::f32::consts::PI;
+
+use types::Vector3;
+
+/// The horizon colour at night
+const NIGHT_FOG: Vector3 = (0.04f32, 0.05f32, 0.09f32);
+/// The ambient light at night
+const NIGHT_AMBIENT: Vector3 = (0.05f32, 0.06f32, 0.1f32);
+/// The horizon colour in daylight
+const DAY_FOG: Vector3 = (0.75f32, 0.85f32, 0.95f32);
+/// The ambient light in daylight
+const DAY_AMBIENT: Vector3 = (0.8f32, 0.9f32, 1f32);
+
+/// A procedural atmosphere lit by a sun that moves with the time of day
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct Sky {
+    /// The time of day in hours from 0 to 24, the sun rises at 6 and sets at 18
+    pub time_of_day: f32,
+    /// Whether the time of day advances each frame
+    pub real_time: bool,
+    /// How many seconds a full day lasts when running in real time
+    pub day_length: f32,
+    /// The haziness of the atmosphere, from 2 for clear to 10 for hazy
+    pub turbidity: f32,
+    /// How bright the sky is drawn
+    pub intensity: f32,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Sky {
+            time_of_day: 12f32,
+            real_time: false,
+            day_length: 1200f32,
+            turbidity: 3f32,
+            intensity: 1f32,
+        }
+    }
+}
+
+impl Sky {
+    /// Advances the time of day by delta seconds when running in real time
+    pub fn update(&mut self, delta: f32) {
+        if self.real_time && self.day_length > 0f32 {
+            self.time_of_day += delta * 24f32 / self.day_length;
+        }
+        self.time_of_day = (self.time_of_day % 24f32 + 24f32) % 24f32;
+    }
+
+    /// The direction towards the sun, rising in +x and setting in -x
+    pub fn sun_dir(&self) -> Vector3 {
+        let angle = (self.time_of_day - 6f32) / 12f32 * PI;
+        normalize((angle.cos(), angle.sin(), -0.3f32 * angle.sin()))
+    }
+
+    /// The colour of the sunlight after passing through the atmosphere
+    pub fn sun_color(&self) -> Vector3 {
+        let elevation = self.sun_dir().1.asin();
+        let above = elevation.max(0f32);
+
+        // relative air mass the light passes through
+        let degrees = above.to_degrees();
+        let air_mass = 1f32 / (above.sin() + 0.15f32 * (degrees + 3.885f32).powf(-1.253f32));
+
+        // blue scatters out first, more so in hazy air
+        let haze = self.turbidity / 3f32;
+        let fade = ((elevation + 0.1f32) / 0.12f32).max(0f32).min(1f32);
+        let extinction = |beta: f32| (-beta * haze * air_mass).exp() * fade;
+        (
+            extinction(0.08f32),
+            extinction(0.18f32),
+            extinction(0.42f32),
+        )
+    }
+
+    /// The colour of light from the whole sky, used to light surfaces in shadow
+    pub fn ambient_color(&self) -> Vector3 {
+        lerp(NIGHT_AMBIENT, DAY_AMBIENT, self.daylight())
+    }
+
+    /// The colour of the sky at the horizon, which distant objects fade to
+    pub fn fog_color(&self) -> Vector3 {
+        let daylight = self.daylight();
+        let sun = self.sun_color();
+        let color = lerp(NIGHT_FOG, DAY_FOG, daylight);
+
+        // warmer towards sunrise and sunset
+        let glow = 0.4f32 * (1f32 - daylight);
+        (
+            (color.0 + sun.0 * glow).min(1f32),
+            (color.1 + sun.1 * glow).min(1f32),
+            (color.2 + sun.2 * glow).min(1f32),
+        )
+    }
+
+    /// How much of the day's light there is, from 0 at night to 1 with the sun high
+    fn daylight(&self) -> f32 {
+        ((self.sun_dir().1 + 0.1f32) / 0.4f32).max(0f32).min(1f32)
+    }
+}
+
+/// Returns the unit vector in the direction of v
+fn normalize(v: Vector3) -> Vector3 {
+    let len = (v.0 * v.0 + v.1 * v.1 + v.2 * v.2).sqrt();
+    (v.0 / len, v.1 / len, v.2 / len)
+}
+
+/// Linearly interpolates between a and b
+fn lerp(a: Vector3, b: Vector3, t: f32) -> Vector3 {
+    (
+        a.0 + (b.0 - a.0) * t,
+        a.1 + (b.1 - a.1) * t,
+        a.2 + (b.2 - a.2) * t,
+    )
+}
@@ -0,0 +1,291 @@
+use glium::backend::Facade;
+use glium::framebuffer::SimpleFrameBuffer;
+use glium::texture::{
+    CubeLayer, Cubemap, MipmapsOption, RawImage2d, Texture2d, UncompressedFloatFormat,
+};
+use glium::uniforms::MagnifySamplerFilter;
+use glium::{BlitTarget, Surface};
+use image;
+use image::hdr::HDRDecoder;
+use image::DynamicImage;
+
+use std::f32::consts::PI;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use types::Vector3;
+
+/// The cube faces in the order they are given
+const FACES: [CubeLayer; 6] = [
+    CubeLayer::PositiveX,
+    CubeLayer::NegativeX,
+    CubeLayer::PositiveY,
+    CubeLayer::NegativeY,
+    CubeLayer::PositiveZ,
+    CubeLayer::NegativeZ,
+];
+
+/// A sky stored in a cubemap, drawn behind everything and passed to material shaders as env_map
+pub struct Skybox {
+    /// The width and height of each face
+    pub size: u32,
+    /// The cubemap the sky is stored in
+    pub cubemap: Cubemap,
+    /// How bright the skybox is drawn
+    pub intensity: f32,
+}
+
+impl Skybox {
+    /// Creates a skybox of a single linear colour, cleared into an 8 bit cubemap so it also
+    /// works on GLES without float render targets
+    pub fn solid<F: Facade>(facade: &F, color: Vector3) -> Result<Skybox, String> {
+        let cubemap = Cubemap::empty_with_format(
+            facade,
+            UncompressedFloatFormat::U8U8U8U8,
+            MipmapsOption::NoMipmap,
+            1,
+        )
+        .map_err(|e| format!("Could not create skybox cubemap: {:?}", e))?;
+
+        for layer in FACES.iter() {
+            SimpleFrameBuffer::new(facade, cubemap.main_level().image(*layer))
+                .map_err(|e| format!("Could not write skybox face: {:?}", e))?
+                .clear_color(color.0, color.1, color.2, 1f32);
+        }
+
+        Ok(Skybox {
+            size: 1,
+            cubemap,
+            intensity: 1f32,
+        })
+    }
+
+    /// Creates a skybox from six square images, in the order +x, -x, +y, -y, +z, -z
+    pub fn from_faces<F: Facade>(facade: &F, faces: &[DynamicImage]) -> Result<Skybox, String> {
+        if faces.len() != 6 {
+            return Err(format!("Expected 6 skybox faces, found {}", faces.len()));
+        }
+
+        let size = faces[0].to_rgb().dimensions().0;
+        let mut data = Vec::with_capacity(6);
+        for face in faces {
+            let face = face.to_rgb();
+            let (width, height) = face.dimensions();
+            if (width, height) != (size, size) {
+                return Err(format!(
+                    "Skybox face is {}x{}, all faces should be {}x{}",
+                    width, height, size, size
+                ));
+            }
+
+            data.push(face.into_raw().into_iter().map(srgb_to_linear).collect());
+        }
+
+        Skybox::from_face_data(facade, size, data)
+    }
+
+    /// Loads a skybox from six square image files, in the order +x, -x, +y, -y, +z, -z
+    pub fn load_faces<F: Facade, P: AsRef<Path>>(
+        facade: &F,
+        paths: &[P],
+    ) -> Result<Skybox, String> {
+        let faces = paths
+            .iter()
+            .map(|p| {
+                image::open(p.as_ref()).map_err(|e| {
+                    format!("Could not load skybox face {}: {}", p.as_ref().display(), e)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Skybox::from_faces(facade, &faces)
+    }
+
+    /// Creates a skybox with faces of size x size from an equirectangular panorama image
+    pub fn from_equirectangular<F: Facade>(
+        facade: &F,
+        panorama: &DynamicImage,
+        size: u32,
+    ) -> Result<Skybox, String> {
+        let panorama = panorama.to_rgb();
+        let dimensions = panorama.dimensions();
+        let texels = panorama
+            .pixels()
+            .map(|p| {
+                [
+                    srgb_to_linear(p.data[0]),
+                    srgb_to_linear(p.data[1]),
+                    srgb_to_linear(p.data[2]),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        Skybox::from_panorama(facade, dimensions, &texels, size)
+    }
+
+    /// Loads a skybox with faces of size x size from an equirectangular panorama file,
+    /// .hdr files keep their full range
+    pub fn load_equirectangular<F: Facade, P: AsRef<Path>>(
+        facade: &F,
+        path: P,
+        size: u32,
+    ) -> Result<Skybox, String> {
+        let path = path.as_ref();
+        let load_err =
+            |e: image::ImageError| format!("Could not load skybox {}: {}", path.display(), e);
+
+        let is_hdr = path.extension().map_or(false, |ext| {
+            ext.to_string_lossy().eq_ignore_ascii_case("hdr")
+        });
+        if !is_hdr {
+            let panorama = image::open(path).map_err(load_err)?;
+            return Skybox::from_equirectangular(facade, &panorama, size);
+        }
+
+        let file = File::open(path)
+            .map_err(|e| format!("Could not load skybox {}: {}", path.display(), e))?;
+        let decoder = HDRDecoder::new(BufReader::new(file)).map_err(load_err)?;
+        let metadata = decoder.metadata();
+        let texels = decoder
+            .read_image_hdr()
+            .map_err(load_err)?
+            .into_iter()
+            .map(|p| p.data)
+            .collect::<Vec<_>>();
+
+        Skybox::from_panorama(facade, (metadata.width, metadata.height), &texels, size)
+    }
+
+    /// Projects linear panorama texels, rows from the top, on to each face of the cube
+    fn from_panorama<F: Facade>(
+        facade: &F,
+        dimensions: (u32, u32),
+        texels: &[[f32; 3]],
+        size: u32,
+    ) -> Result<Skybox, String> {
+        let (width, height) = dimensions;
+        if width == 0 || height == 0 || texels.len() != (width * height) as usize {
+            return Err(format!("Invalid {}x{} skybox panorama", width, height));
+        }
+
+        // bilinear sample, wrapping around horizontally
+        let sample = |u: f32, v: f32| {
+            let x = u * width as f32 - 0.5;
+            let y = (v * height as f32 - 0.5).max(0f32).min((height - 1) as f32);
+            let (x0, y0) = (x.floor(), y.floor());
+            let (fx, fy) = (x - x0, y - y0);
+            let texel = |x: f32, y: f32| {
+                let w = i64::from(width);
+                let x = ((x as i64 % w + w) % w) as u32;
+                let y = (y as u32).min(height - 1);
+                texels[(y * width + x) as usize]
+            };
+
+            let (a, b) = (texel(x0, y0), texel(x0 + 1f32, y0));
+            let (c, d) = (texel(x0, y0 + 1f32), texel(x0 + 1f32, y0 + 1f32));
+            let mut out = [0f32; 3];
+            for (i, val) in out.iter_mut().enumerate() {
+                let top = a[i] + (b[i] - a[i]) * fx;
+                let bottom = c[i] + (d[i] - c[i]) * fx;
+                *val = top + (bottom - top) * fy;
+            }
+            out
+        };
+
+        let data = (0..6)
+            .map(|face| {
+                let mut face_data = Vec::with_capacity((size * size * 3) as usize);
+                for y in 0..size {
+                    for x in 0..size {
+                        let s = 2f32 * (x as f32 + 0.5) / size as f32 - 1f32;
+                        let t = 2f32 * (y as f32 + 0.5) / size as f32 - 1f32;
+                        let (u, v) = panorama_uv(face_direction(face, s, t));
+                        face_data.extend_from_slice(&sample(u, v));
+                    }
+                }
+                face_data
+            })
+            .collect();
+
+        Skybox::from_face_data(facade, size, data)
+    }
+
+    /// Uploads linear rgb data for each face, rows of size texels in texture order
+    fn from_face_data<F: Facade>(
+        facade: &F,
+        size: u32,
+        faces: Vec<Vec<f32>>,
+    ) -> Result<Skybox, String> {
+        let cubemap = Cubemap::empty_with_format(
+            facade,
+            UncompressedFloatFormat::F16F16F16F16,
+            MipmapsOption::NoMipmap,
+            size,
+        )
+        .map_err(|e| format!("Could not create skybox cubemap: {:?}", e))?;
+
+        // each face is uploaded to a texture and blitted into the cubemap
+        for (layer, data) in FACES.iter().zip(faces.into_iter()) {
+            let face = Texture2d::with_format(
+                facade,
+                RawImage2d::from_raw_rgb(data, (size, size)),
+                UncompressedFloatFormat::F16F16F16F16,
+                MipmapsOption::NoMipmap,
+            )
+            .map_err(|e| format!("Could not create skybox face: {:?}", e))?;
+            let target = SimpleFrameBuffer::new(facade, cubemap.main_level().image(*layer))
+                .map_err(|e| format!("Could not write skybox face: {:?}", e))?;
+
+            face.as_surface().blit_whole_color_to(
+                &target,
+                &BlitTarget {
+                    left: 0,
+                    bottom: 0,
+                    width: size as i32,
+                    height: size as i32,
+                },
+                MagnifySamplerFilter::Linear,
+            );
+        }
+
+        Ok(Skybox {
+            size,
+            cubemap,
+            intensity: 1f32,
+        })
+    }
+}
+
+/// Returns the direction through a texel of a cube face, where s and t are from -1 to 1
+///
+/// Faces are in the order +x, -x, +y, -y, +z, -z with t increasing down the face
+pub fn face_direction(face: usize, s: f32, t: f32) -> Vector3 {
+    match face {
+        0 => (1f32, -t, -s),
+        1 => (-1f32, -t, s),
+        2 => (s, 1f32, t),
+        3 => (s, -1f32, -t),
+        4 => (s, -t, 1f32),
+        _ => (-s, -t, -1f32),
+    }
+}
+
+/// Returns the equirectangular panorama uv seen in direction dir
+///
+/// -z is the middle of the panorama and +y the top row
+pub fn panorama_uv(dir: Vector3) -> (f32, f32) {
+    let len = (dir.0 * dir.0 + dir.1 * dir.1 + dir.2 * dir.2).sqrt();
+    let u = 0.5 + dir.0.atan2(-dir.2) / (2f32 * PI);
+    let v = 0.5 - (dir.1 / len).asin() / PI;
+    (u, v)
+}
+
+/// Converts an sRGB colour channel to linear
+pub fn srgb_to_linear(c: u8) -> f32 {
+    let c = f32::from(c) / 255f32;
+    if c <= 0.040_45 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
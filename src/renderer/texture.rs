@@ -16,3 +16,23 @@ macro_rules! load_texture (
         CompressedSrgbTexture2d::new($display, image).unwrap()
     }};
 );
+
+/// Macro for including and loading a texture of linear data, such as a displacement map,
+/// that is sampled without sRGB decoding
+#[macro_export]
+macro_rules! load_data_texture (
+    ($file:expr, $display:expr) => {{
+        use $crate::glium::texture::RawImage2d;
+        use $crate::glium::texture::Texture2d;
+        use std::io::Cursor;
+        use $crate::image;
+
+        let image = image::load(Cursor::new(&include_bytes!($file)[..]),
+                                image::PNG).unwrap().to_rgba();
+        let image_dimensions = image.dimensions();
+        let image = RawImage2d::from_raw_rgba_reversed(image.into_raw().as_slice(),
+                                                       image_dimensions);
+
+        Texture2d::new($display, image).unwrap()
+    }};
+);
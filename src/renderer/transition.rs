@@ -0,0 +1,161 @@
+use glium::uniforms::{AsUniformValue, UniformValue, Uniforms};
+
+/// Denotes how a transition covers the screen
+#[derive(Clone, PartialEq, Debug)]
+pub enum TransitionType {
+    /// Fade out to a colour and back in again
+    Fade((f32, f32, f32)),
+    /// Blend from the last frame before the transition to the new frames
+    Crossfade,
+    /// Wipe to black and back using the red channel of the named texture, darker areas first
+    Wipe(String),
+}
+
+/// Denotes the curve the progress of a transition follows
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Easing {
+    /// Constant speed
+    Linear,
+    /// Starts slow and speeds up
+    EaseIn,
+    /// Starts fast and slows down
+    EaseOut,
+    /// Starts and ends slow
+    EaseInOut,
+}
+
+impl Easing {
+    /// Returns the eased value of t, where t is from 0 to 1
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.max(0f32).min(1f32);
+        match *self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2f32 - t),
+            Easing::EaseInOut => t * t * (3f32 - 2f32 * t),
+        }
+    }
+}
+
+/// Contains all the options for a screen transition
+#[derive(Builder, Clone, PartialEq)]
+#[builder(default)]
+pub struct TransitionOptions {
+    /// How the transition covers the screen
+    pub transition_type: TransitionType,
+    /// How long the whole transition takes in seconds
+    pub duration: f32,
+    /// The curve the transition progress follows
+    pub easing: Easing,
+    /// The width of the soft edge of a wipe, from 0 to 1
+    pub softness: f32,
+}
+
+impl Default for TransitionOptions {
+    fn default() -> Self {
+        TransitionOptions {
+            transition_type: TransitionType::Fade((0f32, 0f32, 0f32)),
+            duration: 1f32,
+            easing: Easing::EaseInOut,
+            softness: 0.1f32,
+        }
+    }
+}
+
+/// A running transition
+pub struct Transition {
+    /// The options the transition was started with
+    pub options: TransitionOptions,
+    /// The seconds since the transition started
+    elapsed: f32,
+    /// The number of frames the transition has been updated for
+    frames: u32,
+    /// Whether the screen has been fully covered yet
+    covered: bool,
+}
+
+impl Transition {
+    /// Creates a transition from its options
+    pub fn new(options: TransitionOptions) -> Transition {
+        Transition {
+            options,
+            elapsed: 0f32,
+            frames: 0,
+            covered: false,
+        }
+    }
+
+    /// Advances the transition by delta seconds
+    ///
+    /// Returns true only on the update the screen becomes fully covered
+    pub fn update(&mut self, delta: f32) -> bool {
+        self.frames += 1;
+
+        // the first frame of a crossfade is kept to blend from
+        if self.capturing() {
+            return false;
+        }
+
+        let was_covered = self.covered;
+        self.elapsed += delta;
+        // a crossfade covers the screen as soon as it has a frame to blend from
+        if self.options.transition_type == TransitionType::Crossfade || self.progress() >= 0.5f32 {
+            self.covered = true;
+        }
+
+        !was_covered && self.covered
+    }
+
+    /// Whether the transition has run its full duration
+    pub fn finished(&self) -> bool {
+        self.progress() >= 1f32
+    }
+
+    /// Whether the screen has been fully covered yet
+    pub fn covered(&self) -> bool {
+        self.covered
+    }
+
+    /// Whether the last frame needs to be kept for the transition to blend from
+    pub fn capturing(&self) -> bool {
+        self.options.transition_type == TransitionType::Crossfade && self.frames <= 1
+    }
+
+    /// How far through the transition is, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        (self.elapsed / self.options.duration.max(0.0001f32)).min(1f32)
+    }
+
+    /// How much of the screen is covered, from 0 to 1
+    pub fn amount(&self) -> f32 {
+        let progress = self.progress();
+        match self.options.transition_type {
+            TransitionType::Crossfade if self.capturing() => 0f32,
+            TransitionType::Crossfade => 1f32 - self.options.easing.apply(progress),
+            _ => self
+                .options
+                .easing
+                .apply(1f32 - (2f32 * progress - 1f32).abs()),
+        }
+    }
+}
+
+impl Uniforms for Transition {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut f: F) {
+        let (transition_type, color) = match self.options.transition_type {
+            TransitionType::Fade(color) => (1, color),
+            TransitionType::Crossfade => (2, (0f32, 0f32, 0f32)),
+            TransitionType::Wipe(_) => (3, (0f32, 0f32, 0f32)),
+        };
+        f("transition_type", UniformValue::SignedInt(transition_type));
+        f(
+            "transition_color",
+            UniformValue::Vec3([color.0, color.1, color.2]),
+        );
+        f("transition_amount", UniformValue::Float(self.amount()));
+        f(
+            "transition_softness",
+            self.options.softness.as_uniform_value(),
+        );
+    }
+}
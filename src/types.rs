@@ -72,6 +72,8 @@ pub struct RenderItem<T: Default> {
     pub vertices: Vec<Vertex>,
     /// The material that will be used for rendering the Item
     pub material: Material,
+    /// How the vertices are assembled into primitives
+    pub topology: Topology,
     /// The instances of this item
     pub instance_transforms: Vec<Transform>,
     /// Whether the item is active/should be rendered
@@ -90,6 +92,7 @@ impl<T: Default> Default for RenderItem<T> {
         RenderItem {
             vertices: Default::default(),
             material: Default::default(),
+            topology: Topology::TrianglesList,
             instance_transforms: Default::default(),
             active: true,
             physics_type: PhysicsType::None,
@@ -101,6 +104,19 @@ impl<T: Default> Default for RenderItem<T> {
 
 unsafe impl<T: Default> Send for RenderItem<T> {}
 
+/// Denotes how the vertices of a RenderItem are assembled into primitives
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub enum Topology {
+    /// Every three vertices form a triangle, drawn through the tessellation stages
+    TrianglesList,
+    /// Every two vertices form a line
+    LinesList,
+    /// Each vertex joins the previous one with a line
+    LineStrip,
+    /// Each vertex is a point
+    Points,
+}
+
 /// Struct for containing material information
 #[derive(Builder, Clone, Serialize, Deserialize, PartialEq)]
 #[builder(default)]
@@ -112,6 +128,16 @@ pub struct Material {
     pub texture_name: Option<String>,
     /// The normal texture that will be used
     pub normal_texture_name: Option<String>,
+    /// How the item is blended with what is behind it
+    pub blend_mode: BlendMode,
+    /// Whether the item writes to the depth buffer
+    pub depth_write: bool,
+    /// Whether the item is hidden by closer items in the depth buffer
+    pub depth_test: bool,
+    /// Which faces of the item are culled
+    pub cull_mode: CullMode,
+    /// How finely the item is tessellated and displaced
+    pub tessellation: TessellationOptions,
 }
 
 impl Default for Material {
@@ -120,10 +146,78 @@ impl Default for Material {
             shader_name: "dist".into(),
             texture_name: None,
             normal_texture_name: None,
+            blend_mode: BlendMode::Opaque,
+            depth_write: true,
+            depth_test: true,
+            cull_mode: CullMode::Clockwise,
+            tessellation: TessellationOptions::default(),
+        }
+    }
+}
+
+impl Material {
+    /// Whether the item needs drawing after opaque items, sorted back to front
+    pub fn is_transparent(&self) -> bool {
+        self.blend_mode != BlendMode::Opaque
+    }
+}
+
+/// Struct for containing the tessellation options of a Material
+#[derive(Builder, Clone, Serialize, Deserialize, PartialEq)]
+#[builder(default)]
+pub struct TessellationOptions {
+    /// How many times the inside of each triangle is subdivided
+    pub inner_level: f32,
+    /// How many times the edges of each triangle are subdivided
+    pub outer_level: f32,
+    /// Whether the levels fall off to 1 with distance from the camera
+    pub adaptive: bool,
+    /// The distance up to which the full levels are used
+    pub near_distance: f32,
+    /// The distance from which triangles are no longer subdivided
+    pub far_distance: f32,
+    /// The data texture whose red channel displaces vertices along their normal
+    pub displacement_texture_name: Option<String>,
+    /// How far a full displacement moves a vertex
+    pub displacement_scale: f32,
+}
+
+impl Default for TessellationOptions {
+    fn default() -> Self {
+        TessellationOptions {
+            inner_level: 1f32,
+            outer_level: 1f32,
+            adaptive: false,
+            near_distance: 10f32,
+            far_distance: 100f32,
+            displacement_texture_name: None,
+            displacement_scale: 1f32,
         }
     }
 }
 
+/// Denotes how a Material is blended with what is behind it
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub enum BlendMode {
+    /// No blending, the item covers what is behind it
+    Opaque,
+    /// Blended using the alpha channel, for glass and similar
+    Alpha,
+    /// Added to what is behind it, for particles and glows
+    Additive,
+}
+
+/// Denotes which faces of a Material are culled
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub enum CullMode {
+    /// Both sides of faces are drawn
+    None,
+    /// Faces wound clockwise are culled
+    Clockwise,
+    /// Faces wound counter clockwise are culled
+    CounterClockwise,
+}
+
 /// struct for abstacting text items to be rendered
 #[derive(Builder, Clone, Serialize, Deserialize, PartialEq)]
 #[builder(default)]
@@ -159,6 +253,24 @@ impl Default for TextItem {
 
 unsafe impl Send for TextItem {}
 
+/// Denotes how the Camera projects the scene
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub enum ProjectionType {
+    /// Perspective projection using the fov
+    Perspective,
+    /// Orthographic projection using the ortho_size
+    Orthographic,
+}
+
+/// Denotes which rotation the Camera is oriented by
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Debug)]
+pub enum OrientationType {
+    /// Oriented by the euler_rot pitch and yaw
+    Euler,
+    /// Oriented by the rot quaternion
+    Quaternion,
+}
+
 /// struct for abstracting the camera state
 #[derive(Builder, Copy, Clone, Serialize, Deserialize, PartialEq)]
 #[builder(default)]
@@ -167,6 +279,23 @@ pub struct Camera {
     pub pos: Vector3,
     /// The euler rotation of the camera
     pub euler_rot: Vector3,
+    /// The quaternion rotation of the camera, used for quaternion orientation
+    pub rot: Quaternion,
+    /// Whether the camera is oriented by euler_rot or rot
+    pub orientation_type: OrientationType,
+    /// How the camera projects the scene
+    pub projection_type: ProjectionType,
+    /// The vertical field of view in degrees, used for perspective projection
+    pub fov: f32,
+    /// The vertical size of the view in world units, used for orthographic projection
+    pub ortho_size: f32,
+    /// The near clipping plane
+    pub znear: f32,
+    /// The far clipping plane
+    pub zfar: f32,
+    /// The normalised screen rect the camera is drawn to as (x, y, width, height),
+    /// with the origin at the bottom left of the screen
+    pub viewport: (f32, f32, f32, f32),
 }
 
 impl Default for Camera {
@@ -174,6 +303,14 @@ impl Default for Camera {
         Camera {
             pos: (0f32, 0f32, 0f32),
             euler_rot: (0f32, 0f32, 0f32),
+            rot: (0f32, 0f32, 0f32, 1f32),
+            orientation_type: OrientationType::Euler,
+            projection_type: ProjectionType::Perspective,
+            fov: 60f32,
+            ortho_size: 10f32,
+            znear: 0.01f32,
+            zfar: 1000f32,
+            viewport: (0f32, 0f32, 1f32, 1f32),
         }
     }
 }
@@ -189,3 +326,20 @@ pub struct ShaderIn {
     pub world_scale: Vector3,
 }
 implement_vertex!(ShaderIn, world_position, world_rotation, world_scale);
+
+/// struct for the previous frame's shader attributes, used to find the velocity of instances
+#[derive(Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PrevShaderIn {
+    /// The world position last frame
+    pub prev_world_position: Vector3,
+    /// The world rotation last frame
+    pub prev_world_rotation: Quaternion,
+    /// The world scale last frame
+    pub prev_world_scale: Vector3,
+}
+implement_vertex!(
+    PrevShaderIn,
+    prev_world_position,
+    prev_world_rotation,
+    prev_world_scale
+);
@@ -5,13 +5,16 @@ use std::f32::consts::PI;
 use std::iter::Sum;
 use std::ops::{Add, Mul};
 
-use types::{Camera, MaterialBuilder, Matrix4, Quaternion, Vector3, Vertex};
+use types::{Camera, MaterialBuilder, Matrix4, ProjectionType, Quaternion, Vector3, Vertex};
 use types::{RenderItem, RenderItemBuilder, TransformBuilder};
 
 use input::{Input, Key};
 
 const TWO_PI: f32 = PI * 2f32;
 
+/// The furthest a camera will pitch up or down, just short of straight up/down
+const MAX_PITCH: f32 = PI / 2f32 - 0.01f32;
+
 /// Returns a Vec<Vertex> that should be converted to buffer and rendered as `TrianglesList`.
 pub fn load_wavefront(data: &[u8]) -> Vec<Vertex> {
     let mut data = ::std::io::BufReader::new(data);
@@ -166,6 +169,78 @@ pub fn to_euler(angle: Quaternion) -> Vector3 {
     (pitch, roll, yaw)
 }
 
+/// returns the two quaternions multiplied together, applying b then a
+pub fn mul_quat(a: Quaternion, b: Quaternion) -> Quaternion {
+    (
+        a.3 * b.0 + a.0 * b.3 + a.1 * b.2 - a.2 * b.1,
+        a.3 * b.1 - a.0 * b.2 + a.1 * b.3 + a.2 * b.0,
+        a.3 * b.2 + a.0 * b.1 - a.1 * b.0 + a.2 * b.3,
+        a.3 * b.3 - a.0 * b.0 - a.1 * b.1 - a.2 * b.2,
+    )
+}
+
+/// returns the quaternion normalised, with an all zero quaternion becoming the identity
+pub fn normalize_quat(q: Quaternion) -> Quaternion {
+    let len = (q.0 * q.0 + q.1 * q.1 + q.2 * q.2 + q.3 * q.3).sqrt();
+    if len <= 0f32 {
+        return (0f32, 0f32, 0f32, 1f32);
+    }
+
+    (q.0 / len, q.1 / len, q.2 / len, q.3 / len)
+}
+
+/// returns a quaternion rotating angle radians around axis
+pub fn axis_angle_quat(axis: Vector3, angle: f32) -> Quaternion {
+    let len = (axis.0 * axis.0 + axis.1 * axis.1 + axis.2 * axis.2).sqrt();
+    if len <= 0f32 {
+        return (0f32, 0f32, 0f32, 1f32);
+    }
+
+    let s = (angle / 2f32).sin() / len;
+    (axis.0 * s, axis.1 * s, axis.2 * s, (angle / 2f32).cos())
+}
+
+/// returns the quaternion for the rotation with the given orthonormal right, up and back axes
+pub fn quat_from_axes(right: Vector3, up: Vector3, back: Vector3) -> Quaternion {
+    let trace = right.0 + up.1 + back.2;
+
+    let q = if trace > 0f32 {
+        let s = (trace + 1f32).sqrt() * 2f32;
+        (
+            (up.2 - back.1) / s,
+            (back.0 - right.2) / s,
+            (right.1 - up.0) / s,
+            0.25f32 * s,
+        )
+    } else if right.0 > up.1 && right.0 > back.2 {
+        let s = (1f32 + right.0 - up.1 - back.2).sqrt() * 2f32;
+        (
+            0.25f32 * s,
+            (up.0 + right.1) / s,
+            (back.0 + right.2) / s,
+            (up.2 - back.1) / s,
+        )
+    } else if up.1 > back.2 {
+        let s = (1f32 + up.1 - right.0 - back.2).sqrt() * 2f32;
+        (
+            (up.0 + right.1) / s,
+            0.25f32 * s,
+            (back.1 + up.2) / s,
+            (back.0 - right.2) / s,
+        )
+    } else {
+        let s = (1f32 + back.2 - right.0 - up.1).sqrt() * 2f32;
+        (
+            (back.0 + right.2) / s,
+            (back.1 + up.2) / s,
+            0.25f32 * s,
+            (right.1 - up.0) / s,
+        )
+    };
+
+    normalize_quat(q)
+}
+
 /// Returns perspective projection matrix given fov, aspect ratio, z near and far
 pub fn build_persp_proj_mat(fov: f32, aspect: f32, znear: f32, zfar: f32) -> Matrix4 {
     let ymax = znear * (fov * (PI / 360.0)).tan();
@@ -191,17 +266,51 @@ pub fn build_persp_proj_mat(fov: f32, aspect: f32, znear: f32, zfar: f32) -> Mat
     ]
 }
 
-/// Returns the model view matrix for a first person view given cam position and rotation
+/// Returns orthographic projection matrix given view height, aspect ratio, z near and far
+pub fn build_ortho_proj_mat(size: f32, aspect: f32, znear: f32, zfar: f32) -> Matrix4 {
+    let half_height = size / 2f32;
+    let half_width = half_height * aspect;
+
+    let depth = zfar - znear;
+    let q = -2.0 / depth;
+    let qn = -(zfar + znear) / depth;
+
+    [
+        [1f32 / half_width, 0.0f32, 0.0f32, 0.0f32],
+        [0.0f32, 1f32 / half_height, 0.0f32, 0.0f32],
+        [0.0f32, 0.0f32, q, 0.0f32],
+        [0.0f32, 0.0f32, qn, 1.0f32],
+    ]
+}
+
+/// Returns the projection matrix for a camera given the aspect ratio
+pub fn build_proj_mat(cam: &Camera, aspect: f32) -> Matrix4 {
+    match cam.projection_type {
+        ProjectionType::Perspective => build_persp_proj_mat(cam.fov, aspect, cam.znear, cam.zfar),
+        ProjectionType::Orthographic => {
+            build_ortho_proj_mat(cam.ortho_size, aspect, cam.znear, cam.zfar)
+        }
+    }
+}
+
+/// Returns the projection matrix offset by jitter in normalised device coordinates
+pub fn jitter_proj_mat(proj: Matrix4, jitter: (f32, f32)) -> Matrix4 {
+    let mut jittered = proj;
+    // add the jitter scaled by w to x and y, so it is constant after the perspective divide
+    for col in jittered.iter_mut() {
+        col[0] += jitter.0 * col[3];
+        col[1] += jitter.1 * col[3];
+    }
+    jittered
+}
+
+/// Returns the model view matrix for a camera given its position and orientation,
+/// using either the euler rotation or quaternion depending on the camera orientation_type
 pub fn build_fp_view_matrix(cam: &Camera) -> Matrix4 {
-    let (sin_yaw, cos_yaw, sin_pitch, cos_pitch) = (
-        cam.euler_rot.1.sin(),
-        cam.euler_rot.1.cos(),
-        cam.euler_rot.0.sin(),
-        cam.euler_rot.0.cos(),
-    );
-    let xaxis = [cos_yaw, 0.0, -sin_yaw];
-    let yaxis = [sin_yaw * sin_pitch, cos_pitch, cos_yaw * sin_pitch];
-    let zaxis = [sin_yaw * cos_pitch, -sin_pitch, cos_pitch * cos_yaw];
+    let (xaxis, yaxis, zaxis) = cam.axes();
+    let xaxis = [xaxis.0, xaxis.1, xaxis.2];
+    let yaxis = [yaxis.0, yaxis.1, yaxis.2];
+    let zaxis = [zaxis.0, zaxis.1, zaxis.2];
 
     let cam_arr = [cam.pos.0, cam.pos.1, cam.pos.2];
 
@@ -218,6 +327,39 @@ pub fn build_fp_view_matrix(cam: &Camera) -> Matrix4 {
     ]
 }
 
+/// Returns the frustum planes for a camera given the aspect ratio,
+/// taking into account its projection and orientation
+pub fn get_cam_frustum_planes(cam: &Camera, aspect: f32) -> Vec<(f32, f32, f32, f32)> {
+    get_frustum_planes(&mul_mat4(
+        build_proj_mat(cam, aspect),
+        build_fp_view_matrix(cam),
+    ))
+}
+
+/// Returns normalised viewports laying out count cameras in a grid for split screen,
+/// ordered from the top left of the screen
+pub fn split_screen_viewports(count: usize) -> Vec<(f32, f32, f32, f32)> {
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let cols = (count as f32).sqrt().ceil() as usize;
+    let rows = (count + cols - 1) / cols;
+    let (width, height) = (1f32 / cols as f32, 1f32 / rows as f32);
+
+    (0..count)
+        .map(|i| {
+            let (col, row) = (i % cols, i / cols);
+            (
+                col as f32 * width,
+                1f32 - (row + 1) as f32 * height,
+                width,
+                height,
+            )
+        })
+        .collect()
+}
+
 /// This method is where data transforms take place due to inputs
 /// for a first person camera
 pub fn handle_fp_inputs(input: &mut Input, cam: &mut Camera) {
@@ -254,19 +396,39 @@ pub fn handle_fp_inputs(input: &mut Input, cam: &mut Camera) {
     cam.euler_rot.0 += input.mouse_axis_motion.1 * MOUSE_SPEED;
     cam.euler_rot.1 += input.mouse_axis_motion.0 * MOUSE_SPEED;
 
-    cam.euler_rot.0 = fix_rot(cam.euler_rot.0);
-    cam.euler_rot.1 = fix_rot(cam.euler_rot.1);
-
-    // make sure euler_rot always between 0 and 2PI
-    fn fix_rot(num: f32) -> f32 {
-        if num < 0f32 {
-            return TWO_PI - num;
-        }
+    cam.euler_rot.0 = clamp_pitch(cam.euler_rot.0);
+    cam.euler_rot.1 = wrap_angle(cam.euler_rot.1);
+}
 
-        num % TWO_PI
+/// Wraps an angle in radians so it is always between 0 and 2PI
+pub fn wrap_angle(num: f32) -> f32 {
+    let wrapped = num % TWO_PI;
+    if wrapped < 0f32 {
+        wrapped + TWO_PI
+    } else {
+        wrapped
     }
 }
 
+/// Clamps a pitch in radians so it never goes past straight up or down,
+/// treating pitches wrapped between 0 and 2PI as looking down past PI
+pub fn clamp_pitch(pitch: f32) -> f32 {
+    let pitch = wrap_angle(pitch + PI) - PI;
+    pitch.max(-MAX_PITCH).min(MAX_PITCH)
+}
+
+/// Rotates a vector by a quaternion, treating an all zero quaternion as no rotation
+pub fn rotate_vec3(rot: Quaternion, v: Vector3) -> Vector3 {
+    let q = [rot.0, rot.1, rot.2];
+    let v_arr = [v.0, v.1, v.2];
+
+    let c = crossp(q, v_arr);
+    let temp = [c[0] + rot.3 * v.0, c[1] + rot.3 * v.1, c[2] + rot.3 * v.2];
+    let c = crossp(q, temp);
+
+    (v.0 + 2f32 * c[0], v.1 + 2f32 * c[1], v.2 + 2f32 * c[2])
+}
+
 /// Test whether an object is in the view frustrum
 pub fn frustrum_test(pos: &Vector3, radius: f32, frustrum_planes: &[(f32, f32, f32, f32)]) -> bool {
     for plane in frustrum_planes {
@@ -336,6 +498,7 @@ pub fn demo(frag_shader: &'static str) {
     use game::*;
     use imgui::Ui;
     use input::Key;
+    use posteffect::PostPassBuilder;
     use shader;
     use types::DefaultTag;
     use utils::handle_fp_inputs;
@@ -352,7 +515,11 @@ pub fn demo(frag_shader: &'static str) {
             frag_shader,
         )
         .unwrap();
-    game.renderer.post_effect.current_shader = "demo";
+    game.renderer.post_effect.passes = vec![PostPassBuilder::default()
+        .name("demo")
+        .shader_name("demo")
+        .build()
+        .unwrap()];
 
     loop {
         // run the engine update
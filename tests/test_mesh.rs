@@ -0,0 +1,46 @@
+extern crate caper;
+extern crate image;
+
+use caper::mesh::{gen_heightmap_mesh, load_heightmap_mesh, save_proc_heightmap};
+use image::DynamicImage;
+use std::env;
+use std::fs;
+
+#[test]
+fn heightmap_mesh_test() {
+    let heightmap = DynamicImage::ImageLuma8(image::ImageBuffer::from_fn(3, 2, |x, _| {
+        image::Luma([if x == 0 { 0u8 } else { 255u8 }])
+    }));
+
+    let vertices = gen_heightmap_mesh(&heightmap, 4f32, 2f32);
+
+    // two quads of two tris each
+    assert_eq!(vertices.len(), 12);
+    assert_eq!(vertices[0].position, [2f32, 4f32, 0f32]);
+    assert_eq!(vertices[1].position, [0f32, 0f32, 0f32]);
+}
+
+/// Rises by a thousandth for each step along x, finer than 8-bit samples can hold
+fn fine_ramp(p: (f32, f32)) -> f32 {
+    p.0 * 0.001f32
+}
+
+#[test]
+fn heightmap_round_trip_test() {
+    let path = env::temp_dir().join("caper_heightmap_round_trip_test.png");
+    save_proc_heightmap(&path, (0f32, 0f32), 4, 1f32, fine_ramp).unwrap();
+    let vertices = load_heightmap_mesh(&path, 1f32, 1f32).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // three quads on each side
+    assert_eq!(vertices.len(), 54);
+    for vertex in &vertices {
+        let expected = fine_ramp((vertex.position[0], vertex.position[2]));
+        assert!(
+            (vertex.position[1] - expected).abs() < 0.0001f32,
+            "{:?} != {}",
+            vertex.position,
+            expected
+        );
+    }
+}
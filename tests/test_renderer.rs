@@ -1 +1,383 @@
+extern crate caper;
 
+use caper::fog::{FogBuilder, FogMode};
+use caper::lut::{parse_cube, CubeData};
+use caper::shader::{gles_source, preprocess};
+use caper::sky::SkyBuilder;
+use caper::skybox::{face_direction, panorama_uv, srgb_to_linear};
+use caper::transition::{Easing, Transition, TransitionOptionsBuilder, TransitionType};
+use std::collections::HashMap;
+
+/// A size 2 .cube that swaps the red and blue channels
+const SWAP_CUBE: &str = "
+    # swaps red and blue
+    TITLE \"swap\"
+    LUT_3D_SIZE 2
+    0 0 0
+    0 0 1
+    0 1 0
+    0 1 1
+    1 0 0
+    1 0 1
+    1 1 0
+    1 1 1
+";
+
+fn assert_color_eq(a: [f32; 3], b: [f32; 3]) {
+    for (a, b) in a.iter().zip(b.iter()) {
+        assert!((a - b).abs() < 0.0001f32, "{:?} != {:?}", a, b);
+    }
+}
+
+#[test]
+fn parse_cube_test() {
+    let cube = parse_cube(SWAP_CUBE).unwrap();
+    assert_eq!(cube.size, 2);
+    assert_eq!(cube.domain_min, [0f32; 3]);
+    assert_eq!(cube.domain_max, [1f32; 3]);
+    assert_eq!(cube.entries.len(), 2 * 2 * 2 * 3);
+
+    // red changes fastest in the file, the entries are rearranged into the strip
+    assert_color_eq(cube.entry(1, 0, 0), [0f32, 0f32, 1f32]);
+    assert_color_eq(cube.entry(0, 1, 0), [0f32, 1f32, 0f32]);
+    assert_color_eq(cube.entry(0, 0, 1), [1f32, 0f32, 0f32]);
+    assert_color_eq(
+        cube.sample([0.25f32, 0.5f32, 1f32]),
+        [1f32, 0.5f32, 0.25f32],
+    );
+}
+
+#[test]
+fn parse_cube_domain_test() {
+    let domain = SWAP_CUBE.replace(
+        "LUT_3D_SIZE 2",
+        "LUT_3D_SIZE 2\nDOMAIN_MIN 0 0 0\nDOMAIN_MAX 2 2 2",
+    );
+    let cube = parse_cube(&domain).unwrap();
+    assert_eq!(cube.domain_max, [2f32; 3]);
+    // the domain scales the input, not the output
+    assert_color_eq(cube.sample([2f32, 0f32, 0f32]), [0f32, 0f32, 1f32]);
+
+    let range = SWAP_CUBE.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nLUT_3D_INPUT_RANGE -1 3");
+    let cube = parse_cube(&range).unwrap();
+    assert_eq!(cube.domain_min, [-1f32; 3]);
+    assert_eq!(cube.domain_max, [3f32; 3]);
+
+    let empty = SWAP_CUBE.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 2\nDOMAIN_MAX 0 1 1");
+    assert!(parse_cube(&empty).is_err());
+}
+
+#[test]
+fn parse_cube_malformed_test() {
+    assert!(parse_cube("").is_err());
+    assert!(parse_cube("LUT_1D_SIZE 2\n0 0 0\n1 1 1").is_err());
+    assert!(parse_cube(&SWAP_CUBE.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE two")).is_err());
+    assert!(parse_cube(&SWAP_CUBE.replace("1 1 1", "1 1")).is_err());
+    assert!(parse_cube(&SWAP_CUBE.replace("1 1 1", "1 1 x")).is_err());
+    assert!(parse_cube(&SWAP_CUBE.replace("1 1 1", "")).is_err());
+    assert!(parse_cube(&SWAP_CUBE.replace("LUT_3D_SIZE 2", "LUT_3D_INPUT_RANGE 0")).is_err());
+}
+
+#[test]
+fn cube_mix_test() {
+    let identity = CubeData::identity(2);
+    assert_color_eq(
+        identity.sample([0.2f32, 0.4f32, 0.6f32]),
+        [0.2f32, 0.4f32, 0.6f32],
+    );
+
+    // halfway between leaving colours alone and swapping red and blue
+    let swap = parse_cube(SWAP_CUBE).unwrap();
+    let mixed = CubeData::mix(&identity, &swap, 0.5f32);
+    assert_color_eq(mixed.sample([1f32, 0f32, 0f32]), [0.5f32, 0f32, 0.5f32]);
+    assert_eq!(CubeData::mix(&identity, &swap, 1f32), swap);
+}
+
+#[test]
+fn preprocess_test() {
+    let mut chunks = HashMap::new();
+    chunks.insert("a".to_string(), "#include \"b\"\nfloat a;".to_string());
+    chunks.insert("b".to_string(), "float b;".to_string());
+
+    // each chunk is included once and numbered as a source string
+    let source = "#version 330\n#include \"a\"\n#include \"b\"\nvoid main() {}";
+    assert_eq!(
+        preprocess(&chunks, source, &[("FOG", "1")]).unwrap(),
+        "#version 330\n#define FOG 1\n// source 1 is chunk \"a\"\n// source 2 is chunk \"b\"\n\
+         #line 2 0\n#line 1 1\n#line 1 2\nfloat b;\n#line 2 1\nfloat a;\n#line 3 0\n\n\
+         void main() {}\n"
+    );
+
+    // nothing is added without includes or defines
+    let plain = "#version 330\nvoid main() {}\n";
+    assert_eq!(preprocess(&chunks, plain, &[]).unwrap(), plain);
+
+    assert!(preprocess(&chunks, "#include \"c\"", &[]).is_err());
+    assert!(preprocess(&chunks, "#include c", &[]).is_err());
+}
+
+#[test]
+fn gles_source_test() {
+    let source = gles_source("#version 330\nout vec4 frag_output;\n");
+    assert!(source.starts_with("#version 300 es\n"));
+    assert!(source.contains("precision highp float;\n"));
+    assert!(source.ends_with("#line 2 0\nout vec4 frag_output;\n"));
+}
+
+fn assert_float_eq(a: f32, b: f32) {
+    assert!((a - b).abs() < 0.0001f32, "{} != {}", a, b);
+}
+
+#[test]
+fn easing_test() {
+    for easing in &[
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ] {
+        assert_float_eq(easing.apply(0f32), 0f32);
+        assert_float_eq(easing.apply(1f32), 1f32);
+        // out of range values are clamped
+        assert_float_eq(easing.apply(-1f32), 0f32);
+        assert_float_eq(easing.apply(2f32), 1f32);
+    }
+
+    assert_float_eq(Easing::Linear.apply(0.25f32), 0.25f32);
+    assert_float_eq(Easing::EaseIn.apply(0.5f32), 0.25f32);
+    assert_float_eq(Easing::EaseOut.apply(0.5f32), 0.75f32);
+    assert_float_eq(Easing::EaseInOut.apply(0.5f32), 0.5f32);
+    assert!(Easing::EaseInOut.apply(0.25f32) < 0.25f32);
+}
+
+#[test]
+fn transition_progress_test() {
+    let options = TransitionOptionsBuilder::default()
+        .transition_type(TransitionType::Fade((0f32, 0f32, 0f32)))
+        .duration(2f32)
+        .easing(Easing::Linear)
+        .build()
+        .unwrap();
+    let mut transition = Transition::new(options);
+    assert_float_eq(transition.progress(), 0f32);
+    assert_float_eq(transition.amount(), 0f32);
+
+    transition.update(0.5f32);
+    assert_float_eq(transition.progress(), 0.25f32);
+    assert_float_eq(transition.amount(), 0.5f32);
+
+    // fully covered half way through
+    transition.update(0.5f32);
+    assert_float_eq(transition.amount(), 1f32);
+
+    transition.update(0.5f32);
+    assert_float_eq(transition.amount(), 0.5f32);
+    assert!(!transition.finished());
+
+    // progress stops at the end
+    transition.update(1f32);
+    assert_float_eq(transition.progress(), 1f32);
+    assert_float_eq(transition.amount(), 0f32);
+    assert!(transition.finished());
+}
+
+#[test]
+fn transition_midpoint_test() {
+    let options = TransitionOptionsBuilder::default()
+        .duration(1f32)
+        .build()
+        .unwrap();
+    let mut transition = Transition::new(options);
+
+    // the midpoint is reported once, on the update that crosses it
+    assert!(!transition.update(0.25f32));
+    assert!(!transition.covered());
+    assert!(transition.update(0.5f32));
+    assert!(transition.covered());
+    assert!(!transition.update(0.1f32));
+    assert!(!transition.update(1f32));
+
+    // a crossfade keeps a frame and then covers the screen straight away
+    let options = TransitionOptionsBuilder::default()
+        .transition_type(TransitionType::Crossfade)
+        .build()
+        .unwrap();
+    let mut transition = Transition::new(options);
+    assert!(!transition.update(0.1f32));
+    assert!(transition.capturing());
+    assert_float_eq(transition.amount(), 0f32);
+    assert!(transition.update(0.1f32));
+    assert!(!transition.capturing());
+    assert!(!transition.update(0.1f32));
+}
+
+#[test]
+fn face_direction_test() {
+    // the centre of each face points down its axis
+    let axes = [
+        (1f32, 0f32, 0f32),
+        (-1f32, 0f32, 0f32),
+        (0f32, 1f32, 0f32),
+        (0f32, -1f32, 0f32),
+        (0f32, 0f32, 1f32),
+        (0f32, 0f32, -1f32),
+    ];
+    for (face, axis) in axes.iter().enumerate() {
+        let dir = face_direction(face, 0f32, 0f32);
+        assert_color_eq([dir.0, dir.1, dir.2], [axis.0, axis.1, axis.2]);
+    }
+
+    // t increases down the side faces
+    assert!(face_direction(4, 0f32, 1f32).1 < 0f32);
+    // neighbouring faces meet at their edges
+    let (a, b) = (
+        face_direction(0, 1f32, 0.5f32),
+        face_direction(5, -1f32, 0.5f32),
+    );
+    assert_color_eq([a.0, a.1, a.2], [b.0, b.1, b.2]);
+    let (a, b) = (
+        face_direction(4, 0.5f32, -1f32),
+        face_direction(2, 0.5f32, 1f32),
+    );
+    assert_color_eq([a.0, a.1, a.2], [b.0, b.1, b.2]);
+}
+
+#[test]
+fn panorama_uv_test() {
+    let uv = |face| panorama_uv(face_direction(face, 0f32, 0f32));
+    let expected = [
+        // +x is to the right of the middle and -x to the left
+        (0, 0.75f32, 0.5f32),
+        (1, 0.25f32, 0.5f32),
+        // +z is on the seam at the right edge
+        (4, 1f32, 0.5f32),
+        // -z is the middle of the panorama
+        (5, 0.5f32, 0.5f32),
+    ];
+    for &(face, u, v) in &expected {
+        let (face_u, face_v) = uv(face);
+        assert_float_eq(face_u, u);
+        assert_float_eq(face_v, v);
+    }
+
+    // the poles are the top and bottom rows
+    assert_float_eq(uv(2).1, 0f32);
+    assert_float_eq(uv(3).1, 1f32);
+}
+
+#[test]
+fn srgb_to_linear_test() {
+    assert_float_eq(srgb_to_linear(0), 0f32);
+    assert_float_eq(srgb_to_linear(255), 1f32);
+    // the linear segment near black
+    assert_float_eq(srgb_to_linear(10), 10f32 / 255f32 / 12.92f32);
+    assert_float_eq(srgb_to_linear(128), 0.215_861f32);
+}
+
+#[test]
+fn fog_mode_index_test() {
+    for mode in &[
+        FogMode::Linear,
+        FogMode::Exponential,
+        FogMode::ExponentialSquared,
+    ] {
+        assert_eq!(FogMode::from_index(mode.mode_index()), *mode);
+    }
+    assert_eq!(FogMode::from_index(7), FogMode::Linear);
+}
+
+#[test]
+fn fog_amount_test() {
+    let cam = (0f32, 0f32, 0f32);
+    let linear = FogBuilder::default()
+        .mode(FogMode::Linear)
+        .start(10f32)
+        .end(30f32)
+        .build()
+        .unwrap();
+    assert_float_eq(linear.amount(cam, (0f32, 0f32, -5f32)), 0f32);
+    assert_float_eq(linear.amount(cam, (0f32, 0f32, -20f32)), 0.5f32);
+    assert_float_eq(linear.amount(cam, (0f32, 0f32, -50f32)), 1f32);
+
+    let exp = FogBuilder::default()
+        .mode(FogMode::Exponential)
+        .density(0.1f32)
+        .build()
+        .unwrap();
+    assert_float_eq(exp.amount(cam, (10f32, 0f32, 0f32)), 1f32 - (-1f32).exp());
+
+    let exp2 = FogBuilder::default()
+        .mode(FogMode::ExponentialSquared)
+        .density(0.1f32)
+        .build()
+        .unwrap();
+    assert_float_eq(exp2.amount(cam, (0f32, 0f32, 20f32)), 1f32 - (-4f32).exp());
+
+    // thinner higher up, but not below y = 0
+    let height = FogBuilder::default()
+        .end(10f32)
+        .height_falloff(0.5f32)
+        .build()
+        .unwrap();
+    assert_float_eq(height.amount(cam, (20f32, -2f32, 0f32)), 1f32);
+    assert_float_eq(
+        height.amount((0f32, 2f32, 0f32), (20f32, 2f32, 0f32)),
+        (-1f32).exp(),
+    );
+
+    let inactive = FogBuilder::default().active(false).build().unwrap();
+    assert_float_eq(inactive.amount(cam, (0f32, 0f32, -500f32)), 0f32);
+}
+
+#[test]
+fn sun_dir_test() {
+    let mut sky = SkyBuilder::default().time_of_day(12f32).build().unwrap();
+    assert!(sky.sun_dir().1 > 0.9f32);
+
+    sky.time_of_day = 0f32;
+    assert!(sky.sun_dir().1 < -0.9f32);
+    assert_eq!(sky.sun_color(), (0f32, 0f32, 0f32));
+
+    // rises in +x and sets in -x
+    sky.time_of_day = 6f32;
+    assert!(sky.sun_dir().0 > 0.9f32);
+    sky.time_of_day = 18f32;
+    assert!(sky.sun_dir().0 < -0.9f32);
+
+    // wraps around past midnight
+    sky.real_time = true;
+    sky.day_length = 24f32;
+    sky.time_of_day = 23f32;
+    sky.update(2f32);
+    assert_float_eq(sky.time_of_day, 1f32);
+
+    // only advances in real time
+    sky.real_time = false;
+    sky.update(2f32);
+    assert_float_eq(sky.time_of_day, 1f32);
+}
+
+#[test]
+fn sky_color_test() {
+    let noon = SkyBuilder::default().time_of_day(12f32).build().unwrap();
+    let dusk = SkyBuilder::default().time_of_day(17.5f32).build().unwrap();
+    let night = SkyBuilder::default().time_of_day(0f32).build().unwrap();
+
+    // the sun reddens as it sets
+    let (noon_sun, dusk_sun) = (noon.sun_color(), dusk.sun_color());
+    assert!(noon_sun.2 > dusk_sun.2);
+    assert!(dusk_sun.0 > dusk_sun.2);
+
+    // hazier air lets less light through
+    let hazy = SkyBuilder::default()
+        .time_of_day(12f32)
+        .turbidity(10f32)
+        .build()
+        .unwrap();
+    assert!(hazy.sun_color().2 < noon_sun.2);
+
+    // darker at night
+    let brightness = |c: (f32, f32, f32)| c.0 + c.1 + c.2;
+    assert!(brightness(night.ambient_color()) < brightness(noon.ambient_color()));
+    assert!(brightness(night.fog_color()) < brightness(noon.fog_color()));
+}
@@ -1 +1,58 @@
+extern crate caper;
 
+use caper::camera::FollowControllerBuilder;
+use caper::types::{Camera, DefaultTag, RenderItem, RenderItemBuilder, TransformBuilder};
+use caper::utils::{clamp_pitch, wrap_angle};
+use std::f32::consts::PI;
+
+const TWO_PI: f32 = PI * 2f32;
+
+#[test]
+fn wrap_angle_test() {
+    assert!((wrap_angle(-0.5f32) - (TWO_PI - 0.5f32)).abs() < 0.0001f32);
+    assert!((wrap_angle(TWO_PI + 0.5f32) - 0.5f32).abs() < 0.0001f32);
+    assert!((wrap_angle(1f32) - 1f32).abs() < 0.0001f32);
+}
+
+#[test]
+fn clamp_pitch_test() {
+    assert!((clamp_pitch(0.5f32) - 0.5f32).abs() < 0.0001f32);
+    assert!(clamp_pitch(10f32) < PI / 2f32);
+    // a wrapped pitch just under 2PI is looking slightly down, not straight up
+    assert!((clamp_pitch(TWO_PI - 0.5f32) + 0.5f32).abs() < 0.0001f32);
+    assert!((clamp_pitch(wrap_angle(-0.5f32)) + 0.5f32).abs() < 0.0001f32);
+}
+
+#[test]
+fn follow_controller_test() {
+    let items: Vec<RenderItem<DefaultTag>> = vec![RenderItemBuilder::default()
+        .name("player")
+        .instance_transforms(vec![TransformBuilder::default()
+            .pos((10f32, 0f32, 0f32))
+            .build()
+            .unwrap()])
+        .build()
+        .unwrap()];
+
+    let follow = FollowControllerBuilder::default()
+        .item_name("player")
+        .offset((0f32, 0f32, 5f32))
+        .look_offset((0f32, 0f32, 0f32))
+        .smoothing(1000f32)
+        .build()
+        .unwrap();
+    let mut cam = Camera::default();
+    follow.follow(&items, &mut cam, 1f32).unwrap();
+
+    // caught up with the offset and looking back along -z at the target
+    assert!((cam.pos.0 - 10f32).abs() < 0.001f32);
+    assert!((cam.pos.2 - 5f32).abs() < 0.001f32);
+    assert!(cam.euler_rot.0.abs() < 0.001f32);
+    assert!(wrap_angle(cam.euler_rot.1 + 0.001f32) < 0.01f32);
+
+    let missing = FollowControllerBuilder::default()
+        .item_name("enemy")
+        .build()
+        .unwrap();
+    assert!(missing.follow(&items, &mut cam, 1f32).is_err());
+}